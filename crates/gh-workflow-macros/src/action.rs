@@ -0,0 +1,262 @@
+//! Generates a typed wrapper for an action from its `action.yml` metadata.
+
+use std::path::PathBuf;
+
+use heck::ToSnakeCase;
+use indexmap::IndexMap;
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use serde::Deserialize;
+use syn::{parse_macro_input, DeriveInput, Ident, LitStr};
+
+/// The parts of `action.yml` that the wrapper is generated from.
+#[derive(Deserialize)]
+struct Metadata {
+    name: String,
+    #[serde(default)]
+    inputs: IndexMap<String, Input>,
+    #[serde(default)]
+    outputs: IndexMap<String, Output>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Input {
+    description: Option<String>,
+    #[serde(default)]
+    required: Option<serde_yml::Value>,
+    #[serde(default)]
+    default: Option<serde_yml::Value>,
+    deprecation_message: Option<String>,
+}
+
+impl Input {
+    /// Some actions quote the `required` flag, so both `true` and `'true'`
+    /// are accepted.
+    fn is_required(&self) -> bool {
+        match &self.required {
+            Some(serde_yml::Value::Bool(required)) => *required,
+            Some(serde_yml::Value::String(required)) => required == "true",
+            _ => false,
+        }
+    }
+
+    fn default(&self) -> Option<String> {
+        match self.default.as_ref()? {
+            serde_yml::Value::String(default) => Some(default.clone()),
+            serde_yml::Value::Null => None,
+            default => Some(serde_yml::to_string(default).ok()?.trim().to_string()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Output {
+    description: Option<String>,
+}
+
+/// Names of the generated methods that inputs must not shadow.
+const RESERVED: &[&str] = &["new", "id", "outputs"];
+
+/// Turns an input or output name into a method name.
+fn method_name(name: &str) -> Ident {
+    let name = name.to_snake_case();
+    if RESERVED.contains(&name.as_str()) || syn::parse_str::<Ident>(&name).is_err() {
+        format_ident!("{}_", name)
+    } else {
+        format_ident!("{}", name)
+    }
+}
+
+pub fn expand(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+
+    let mut path: Option<LitStr> = None;
+    let mut uses: Option<LitStr> = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("path") {
+            path = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("uses") {
+            uses = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("expected `path` or `uses`"))
+        }
+    });
+    parse_macro_input!(attr with parser);
+
+    let (Some(path), Some(uses)) = (path, uses) else {
+        return syn::Error::new(
+            input.ident.span(),
+            "#[action] requires `path = \"...\"` and `uses = \"owner/repo@version\"`",
+        )
+        .to_compile_error()
+        .into();
+    };
+
+    match generate(&input, &path, &uses) {
+        Ok(expanded) => expanded,
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn generate(input: &DeriveInput, path: &LitStr, uses: &LitStr) -> syn::Result<TokenStream> {
+    if !matches!(&input.data, syn::Data::Struct(data) if data.fields.is_empty()) {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "#[action] can only be used on unit structs",
+        ));
+    }
+
+    let root = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let file = PathBuf::from(root).join(path.value());
+    let file_str = file.display().to_string();
+    let content = std::fs::read_to_string(&file)
+        .map_err(|e| syn::Error::new(path.span(), format!("failed to read {file_str}: {e}")))?;
+    let metadata: Metadata = serde_yml::from_str(&content)
+        .map_err(|e| syn::Error::new(path.span(), format!("invalid action metadata: {e}")))?;
+
+    let (owner, repo, version) = uses
+        .value()
+        .split_once('@')
+        .and_then(|(action, version)| {
+            let (owner, repo) = action.split_once('/')?;
+            Some((owner.to_string(), repo.to_string(), version.to_string()))
+        })
+        .ok_or_else(|| syn::Error::new(uses.span(), "expected `owner/repo@version`"))?;
+
+    let attrs = &input.attrs;
+    let vis = &input.vis;
+    let name = &input.ident;
+    let outputs_name = format_ident!("{}Outputs", name);
+    let step_name = &metadata.name;
+
+    let required = metadata
+        .inputs
+        .iter()
+        .filter(|(_, input)| input.is_required() && input.default().is_none())
+        .map(|(key, _)| (key, method_name(key)))
+        .collect::<Vec<_>>();
+    let required_keys = required.iter().map(|(key, _)| key);
+    let required_idents = required.iter().map(|(_, ident)| ident).collect::<Vec<_>>();
+
+    let setters = metadata.inputs.iter().map(|(key, input)| {
+        let ident = method_name(key);
+        let description = input
+            .description
+            .as_deref()
+            .map(str::trim)
+            .unwrap_or_default();
+        let default = input
+            .default()
+            .map(|default| format!("Defaults to `{default}`."));
+        let default = default.iter();
+        let deprecated = input
+            .deprecation_message
+            .as_deref()
+            .map(str::trim)
+            .map(|note| quote! { #[deprecated(note = #note)] });
+
+        quote! {
+            #[doc = #description]
+            #(#[doc = ""] #[doc = #default])*
+            #deprecated
+            pub fn #ident<V: Into<::gh_workflow::__private::Value>>(mut self, value: V) -> Self {
+                self.input = self.input.add(#key, value);
+                self
+            }
+        }
+    });
+
+    let defaults = metadata
+        .inputs
+        .iter()
+        .filter_map(|(key, input)| input.default().map(|default| quote! { (#key, #default) }));
+
+    let output_accessors = metadata.outputs.iter().map(|(key, output)| {
+        let ident = method_name(key);
+        let description = output
+            .description
+            .as_deref()
+            .map(str::trim)
+            .unwrap_or_default();
+
+        quote! {
+            #[doc = #description]
+            pub fn #ident(&self) -> ::gh_workflow::ctx::Context<String> {
+                ::gh_workflow::ctx::Context::steps()
+                    .get(self.id.as_str())
+                    .outputs()
+                    .get(#key)
+            }
+        }
+    });
+
+    let new_doc = format!("Creates a new `{name}` step with the required inputs.");
+    let outputs_doc = format!("The outputs of a [`{name}`] step.");
+
+    Ok(quote! {
+        #(#attrs)*
+        #[derive(Clone)]
+        #vis struct #name {
+            id: Option<String>,
+            input: ::gh_workflow::Input,
+        }
+
+        impl #name {
+            /// The inputs that have a default value in the action metadata.
+            pub const DEFAULTS: &'static [(&'static str, &'static str)] = &[#(#defaults),*];
+
+            #[doc = #new_doc]
+            pub fn new(
+                #(#required_idents: impl Into<::gh_workflow::__private::Value>),*
+            ) -> Self {
+                Self {
+                    id: None,
+                    input: ::gh_workflow::Input::default()#(.add(#required_keys, #required_idents))*,
+                }
+            }
+
+            /// Sets the id of the step.
+            pub fn id<T: ToString>(mut self, id: T) -> Self {
+                self.id = Some(id.to_string());
+                self
+            }
+
+            /// The outputs of the step. Selecting them needs the id of the
+            /// step, `None` until one is set with `id`.
+            pub fn outputs(&self) -> Option<#outputs_name> {
+                Some(#outputs_name { id: self.id.clone()? })
+            }
+
+            #(#setters)*
+        }
+
+        #[doc = #outputs_doc]
+        #vis struct #outputs_name {
+            id: String,
+        }
+
+        impl #outputs_name {
+            #(#output_accessors)*
+        }
+
+        impl From<#name> for ::gh_workflow::Step<::gh_workflow::Use> {
+            fn from(value: #name) -> Self {
+                // Recompiles the wrapper whenever the metadata changes.
+                const _: &str = include_str!(#file_str);
+
+                let mut step = ::gh_workflow::Step::new(#step_name).uses(#owner, #repo, #version);
+                if let Some(id) = value.id {
+                    step = step.id(id);
+                }
+                if !value.input.is_empty() {
+                    step = step.with(value.input);
+                }
+                step
+            }
+        }
+    }
+    .into())
+}
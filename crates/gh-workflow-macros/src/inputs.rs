@@ -0,0 +1,293 @@
+//! Derives typed `workflow_dispatch` and `workflow_call` inputs.
+
+use heck::ToKebabCase;
+use proc_macro::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields, Lit};
+
+/// Collects the doc comments of an item into a single description.
+fn description(attrs: &[Attribute]) -> String {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(meta) => match &meta.value {
+                syn::Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(doc) => Some(doc.value().trim().to_string()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim()
+        .to_string()
+}
+
+/// Converts the `default` of an input into a typed value, along with the
+/// type of the literal and its text for the compile time checks.
+fn default_value(lit: &Lit) -> syn::Result<(proc_macro2::TokenStream, &'static str, String)> {
+    Ok(match lit {
+        Lit::Str(value) => {
+            let text = value.value();
+            (
+                quote! { ::gh_workflow::__private::Value::from(#text) },
+                "string",
+                text,
+            )
+        }
+        Lit::Bool(value) => {
+            let value = value.value;
+            (
+                quote! { ::gh_workflow::__private::Value::from(#value) },
+                "boolean",
+                value.to_string(),
+            )
+        }
+        Lit::Int(value) => {
+            let text = value.base10_digits().to_string();
+            let value = value.base10_parse::<i64>()?;
+            (
+                quote! { ::gh_workflow::__private::Value::from(#value) },
+                "number",
+                text,
+            )
+        }
+        Lit::Float(value) => {
+            let text = value.base10_digits().to_string();
+            let value = value.base10_parse::<f64>()?;
+            (
+                quote! { ::gh_workflow::__private::Value::from(#value) },
+                "number",
+                text,
+            )
+        }
+        lit => return Err(syn::Error::new(lit.span(), "unsupported default")),
+    })
+}
+
+/// The options of a field set with `#[input(...)]`.
+#[derive(Default)]
+struct FieldOptions {
+    rename: Option<String>,
+    default: Option<Lit>,
+}
+
+fn field_options(attrs: &[Attribute]) -> syn::Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("input")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                options.rename = Some(value.value());
+                Ok(())
+            } else if meta.path.is_ident("default") {
+                options.default = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `rename` or `default`"))
+            }
+        })?;
+    }
+    Ok(options)
+}
+
+pub fn expand_inputs(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match inputs(&input) {
+        Ok(expanded) => expanded,
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn inputs(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let vis = &input.vis;
+    let context_trait = format_ident!("{}Context", name);
+
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            name.span(),
+            "#[derive(WorkflowInputs)] can only be used with structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new(
+            name.span(),
+            "#[derive(WorkflowInputs)] only supports structs with named fields",
+        ));
+    };
+
+    let mut dispatch = Vec::new();
+    let mut call = Vec::new();
+    let mut with = Vec::new();
+    let mut accessors = Vec::new();
+    let mut signatures = Vec::new();
+    let mut checks = Vec::new();
+
+    for field in &fields.named {
+        let ident = field.ident.as_ref().expect("named field");
+        let ty = &field.ty;
+        let options = field_options(&field.attrs)?;
+        let key = options.rename.unwrap_or_else(|| ident.to_string());
+        let description = description(&field.attrs);
+        let has_default = options.default.is_some();
+        let default = match &options.default {
+            Some(lit) => {
+                let (value, kind, text) = default_value(lit)?;
+                let message = format!(
+                    "the default of `{key}` doesn't match its type, or isn't one of its options"
+                );
+                checks.push(quote_spanned! {lit.span()=>
+                    const _: () = assert!(
+                        ::gh_workflow::is_valid_default(
+                            <#ty as ::gh_workflow::WorkflowInput>::TYPE,
+                            <#ty as ::gh_workflow::WorkflowInput>::OPTIONS,
+                            #kind,
+                            #text,
+                        ),
+                        #message,
+                    );
+                });
+                quote! { Some(#value) }
+            }
+            None => quote! { None },
+        };
+
+        dispatch.push(quote! {
+            inputs.insert(
+                #key.to_string(),
+                ::gh_workflow::WorkflowDispatchInput {
+                    description: #description.to_string(),
+                    required: <#ty as ::gh_workflow::WorkflowInput>::REQUIRED && !#has_default,
+                    input_type: <#ty as ::gh_workflow::WorkflowInput>::TYPE.to_string(),
+                    default: #default,
+                    options: <#ty as ::gh_workflow::WorkflowInput>::options(),
+                    ..Default::default()
+                },
+            );
+        });
+
+        call.push(quote! {
+            inputs.insert(
+                #key.to_string(),
+                ::gh_workflow::WorkflowCallInput {
+                    description: #description.to_string(),
+                    required: <#ty as ::gh_workflow::WorkflowInput>::REQUIRED && !#has_default,
+                    input_type: ::gh_workflow::workflow_call_type(
+                        <#ty as ::gh_workflow::WorkflowInput>::TYPE,
+                    )
+                    .to_string(),
+                    default: #default,
+                    ..Default::default()
+                },
+            );
+        });
+
+        with.push(quote! {
+            if let Some(value) = ::gh_workflow::WorkflowInput::to_value(&self.#ident) {
+                input = input.add(#key, value);
+            }
+        });
+
+        let doc = format!("The `inputs.{key}` context.");
+        signatures.push(quote! {
+            #[doc = #doc]
+            fn #ident(&self) -> ::gh_workflow::ctx::Context<#ty>;
+        });
+        accessors.push(quote! {
+            fn #ident(&self) -> ::gh_workflow::ctx::Context<#ty> {
+                self.__select(#key)
+            }
+        });
+    }
+
+    let trait_doc = format!("Typed accessors for the `inputs` context of [`{name}`].");
+
+    Ok(quote! {
+        #(#checks)*
+
+        impl ::gh_workflow::WorkflowInputs for #name {
+            fn workflow_dispatch() -> ::gh_workflow::WorkflowDispatch {
+                let mut inputs = ::gh_workflow::__private::IndexMap::new();
+                #(#dispatch)*
+                ::gh_workflow::WorkflowDispatch { inputs, ..Default::default() }
+            }
+
+            fn workflow_call() -> ::gh_workflow::WorkflowCall {
+                let mut inputs = ::gh_workflow::__private::IndexMap::new();
+                #(#call)*
+                ::gh_workflow::WorkflowCall { inputs, ..Default::default() }
+            }
+
+            fn to_input(&self) -> ::gh_workflow::Input {
+                let mut input = ::gh_workflow::Input::default();
+                #(#with)*
+                input
+            }
+        }
+
+        #[doc = #trait_doc]
+        #vis trait #context_trait {
+            #(#signatures)*
+        }
+
+        impl #context_trait for ::gh_workflow::ctx::Context<#name> {
+            #(#accessors)*
+        }
+    }
+    .into())
+}
+
+pub fn expand_choice(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match choice(&input) {
+        Ok(expanded) => expanded,
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn choice(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new(
+            name.span(),
+            "#[derive(InputChoice)] can only be used with enums",
+        ));
+    };
+
+    let mut options = Vec::new();
+    let mut arms = Vec::new();
+    for variant in &data.variants {
+        if !variant.fields.is_empty() {
+            return Err(syn::Error::new(
+                variant.ident.span(),
+                "#[derive(InputChoice)] only supports unit variants",
+            ));
+        }
+        let ident = &variant.ident;
+        let value = field_options(&variant.attrs)?
+            .rename
+            .unwrap_or_else(|| ident.to_string().to_kebab_case());
+        options.push(value.clone());
+        arms.push(quote! { Self::#ident => #value });
+    }
+
+    Ok(quote! {
+        impl ::gh_workflow::WorkflowInput for #name {
+            const TYPE: &'static str = "choice";
+
+            const OPTIONS: &'static [&'static str] = &[#(#options),*];
+
+            fn to_value(&self) -> Option<::gh_workflow::__private::Value> {
+                let value = match self {
+                    #(#arms),*
+                };
+                Some(::gh_workflow::__private::Value::from(value))
+            }
+        }
+    }
+    .into())
+}
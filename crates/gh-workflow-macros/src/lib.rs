@@ -3,48 +3,178 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::{parse_macro_input, Data, DeriveInput, Fields};
 
-#[proc_macro_derive(Context)]
+mod action;
+mod inputs;
+
+/// Derives typed selectors on `Context<Self>`, one per field.
+///
+/// - `#[context(root)]` or `#[context(root = "name")]` on the struct adds a
+///   constructor for a top-level context, named after the struct by default.
+/// - `#[context(rename = "name")]` on a field selects `name` instead of the
+///   field name, for eg: a `ref_` field selecting `ref`.
+/// - Map fields (`HashMap`, `IndexMap` or `BTreeMap`) return a
+///   `Context<Map<V>>` whose entries are selected with `.get(key)`.
+#[proc_macro_derive(Context, attributes(context))]
 pub fn derive_expr(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    let struct_name = input.ident;
-    let ctor_name = struct_name.to_string().to_snake_case();
-    let ctor_id = syn::Ident::new(&ctor_name, struct_name.span());
-
-    // Ensure it's a struct and get its fields
-    let fields = if let Data::Struct(data_struct) = input.data {
-        if let Fields::Named(fields) = data_struct.fields {
-            fields
-        } else {
-            panic!("#[derive(Context)] only supports structs with named fields")
+    match context(&input) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// The value of a `#[context(name)]` or `#[context(name = "value")]` option.
+/// `name` is the only option allowed on `attrs`, the other one is an error.
+fn context_option(attrs: &[syn::Attribute], name: &str) -> syn::Result<Option<Option<String>>> {
+    let mut value = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("context")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident(name) {
+                value = Some(if meta.input.peek(syn::Token![=]) {
+                    let lit: syn::LitStr = meta.value()?.parse()?;
+                    Some(lit.value())
+                } else {
+                    None
+                });
+                Ok(())
+            } else if meta.path.is_ident("root") {
+                Err(syn::Error::new_spanned(
+                    &meta.path,
+                    "`root` is only allowed on the struct",
+                ))
+            } else if meta.path.is_ident("rename") {
+                Err(syn::Error::new_spanned(
+                    &meta.path,
+                    "`rename` is only allowed on a field",
+                ))
+            } else {
+                Err(meta.error("expected `root` or `rename`"))
+            }
+        })?;
+    }
+    Ok(value)
+}
+
+/// Returns the value type of a map field.
+fn map_value(ty: &syn::Type) -> Option<&syn::Type> {
+    let syn::Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if !["HashMap", "IndexMap", "BTreeMap"].contains(&segment.ident.to_string().as_str()) {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.iter().nth(1)? {
+        syn::GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    }
+}
+
+fn context(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let struct_name = &input.ident;
+
+    let Data::Struct(data_struct) = &input.data else {
+        return Err(syn::Error::new(
+            struct_name.span(),
+            "#[derive(Context)] can only be used with structs",
+        ));
+    };
+    let fields = match &data_struct.fields {
+        Fields::Named(fields) => fields.named.iter().collect::<Vec<_>>(),
+        Fields::Unit => Vec::new(),
+        Fields::Unnamed(_) => {
+            return Err(syn::Error::new(
+                struct_name.span(),
+                "#[derive(Context)] only supports structs with named fields",
+            ))
         }
-    } else {
-        panic!("#[derive(Context)] can only be used with structs");
     };
 
     // Generate methods for each field
-    let methods = fields.named.iter().map(|field| {
+    let mut methods = Vec::new();
+    for field in fields {
         let field_name = &field.ident;
-        let field_type = &field.ty;
-        let field_name_str = field_name.as_ref().unwrap().to_string();
-        quote! {
+        let field_name_str = match context_option(&field.attrs, "rename")? {
+            Some(Some(rename)) => rename,
+            Some(None) => {
+                return Err(syn::Error::new(
+                    field
+                        .ident
+                        .as_ref()
+                        .map_or_else(|| struct_name.span(), |i| i.span()),
+                    "expected `rename = \"...\"`",
+                ))
+            }
+            None => field_name.as_ref().unwrap().to_string(),
+        };
+        let field_type = match map_value(&field.ty) {
+            Some(value) => quote! { Map<#value> },
+            None => {
+                let ty = &field.ty;
+                quote! { #ty }
+            }
+        };
+        methods.push(quote! {
             pub fn #field_name(&self) -> Context<#field_type> {
                 self.select::<#field_type>(#field_name_str)
             }
+        });
+    }
+
+    let ctor = match context_option(&input.attrs, "root")? {
+        Some(root) => {
+            let ctor_name = root.unwrap_or_else(|| struct_name.to_string().to_snake_case());
+            let ctor_id = syn::Ident::new(&ctor_name, struct_name.span());
+            quote! {
+                pub fn #ctor_id() -> Self {
+                    Self::new().select(#ctor_name)
+                }
+            }
         }
-    });
+        None => quote! {},
+    };
 
     // Generate the output code
-    let expanded = quote! {
+    Ok(quote! {
         impl Context<#struct_name> {
             #(#methods)*
 
-            pub fn #ctor_id() -> Self {
-                Context::<Github>::new().select(stringify!(#ctor_name))
-            }
+            #ctor
         }
-    };
+    })
+}
 
-    // eprintln!("Generated code:\n{}", expanded);
+/// Generates a typed wrapper for an action from its vendored `action.yml`.
+/// The unit struct is replaced by a builder with a setter per input, a
+/// constructor taking the required inputs and typed output accessors. The
+/// outputs are only available once the step has an id set with `.id(...)`.
+///
+/// ```ignore
+/// #[action(path = "actions/cache.yml", uses = "actions/cache@v4")]
+/// pub struct Cache;
+/// ```
+#[proc_macro_attribute]
+pub fn action(attr: TokenStream, item: TokenStream) -> TokenStream {
+    action::expand(attr, item)
+}
+
+/// Derives `WorkflowInputs` for a struct, declaring one workflow input per
+/// field. Doc comments become descriptions, `Option` fields are optional and
+/// `#[input(default = ..., rename = "...")]` adjusts a single input. The
+/// default must be a literal of the input's type, or one of the options of a
+/// choice, which is checked at compile time. A `<Name>Context` trait with an
+/// `inputs.<field>` accessor per field is generated alongside.
+#[proc_macro_derive(WorkflowInputs, attributes(input))]
+pub fn derive_workflow_inputs(input: TokenStream) -> TokenStream {
+    inputs::expand_inputs(input)
+}
 
-    TokenStream::from(expanded)
+/// Derives `WorkflowInput` for an enum of unit variants, used as a `choice`
+/// input whose options are the kebab-cased variant names.
+#[proc_macro_derive(InputChoice, attributes(input))]
+pub fn derive_input_choice(input: TokenStream) -> TokenStream {
+    inputs::expand_choice(input)
 }
@@ -0,0 +1,250 @@
+//! A release workflow for CLIs that attaches prebuilt binaries to a GitHub
+//! release. Every target is built by a [`CrossBuild`] matrix and packaged as
+//! `<binary>-<tag>-<target>.tar.gz`, or `.zip` on Windows. The archives are
+//! uploaded to the release with a `SHA256SUMS` file and, optionally, build
+//! provenance attestations.
+
+use derive_setters::Setters;
+use gh_workflow::cross::CrossBuild;
+use gh_workflow::error::Result;
+use gh_workflow::generate::Generate;
+use gh_workflow::toolchain::Target;
+use gh_workflow::{Workflow as GHWorkflow, *};
+
+/// The directory the archives are written to.
+const DIST: &str = "dist";
+
+/// The prefix of the artifacts that carry the archives between jobs.
+const ARTIFACT_PREFIX: &str = "binaries-";
+
+/// The event that starts a release.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReleaseTrigger {
+    /// Pushing a tag that matches [`BinaryRelease::tags`], the GitHub
+    /// release is created for the tag.
+    #[default]
+    Tag,
+
+    /// Publishing a GitHub release, the archives are attached to it.
+    Published,
+}
+
+#[derive(Clone, Setters)]
+#[setters(strip_option, into)]
+pub struct BinaryRelease {
+    /// Name of the workflow.
+    pub name: String,
+
+    /// The name of the binary to package, without the `.exe` extension.
+    pub binary: String,
+
+    /// Builds the binary for every target.
+    pub build: CrossBuild,
+
+    /// Extra files added to every archive, for eg: `README.md`.
+    pub include: Vec<String>,
+
+    /// The event that starts a release.
+    pub trigger: ReleaseTrigger,
+
+    /// Tag patterns that trigger a release when pushed, used with
+    /// [`ReleaseTrigger::Tag`].
+    pub tags: Vec<String>,
+
+    /// Generates build provenance attestations for the archives.
+    pub attest: bool,
+}
+
+impl BinaryRelease {
+    /// Creates a new `BinaryRelease` of `binary` for `targets`.
+    pub fn new<T: ToString>(binary: T, targets: impl IntoIterator<Item = Target>) -> Self {
+        Self {
+            name: "Binary Release".to_string(),
+            binary: binary.to_string(),
+            build: CrossBuild::new(targets),
+            include: Vec::new(),
+            trigger: ReleaseTrigger::default(),
+            tags: vec!["v*".to_string()],
+            attest: false,
+        }
+    }
+
+    /// Adds a file to every archive.
+    pub fn add_include<T: ToString>(mut self, path: T) -> Self {
+        self.include.push(path.to_string());
+        self
+    }
+
+    /// Adds a tag pattern that triggers a release when pushed.
+    pub fn add_tag<T: ToString>(mut self, tag: T) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    /// Generates the workflow file.
+    pub fn generate(&self) -> Result<()> {
+        Generate::new(self.to_workflow())
+            .name("binary-release.yml")
+            .validate_schema(true)
+            .generate()
+    }
+
+    /// Converts the release into a Github workflow.
+    pub fn to_workflow(&self) -> GHWorkflow {
+        let event = match self.trigger {
+            ReleaseTrigger::Tag => Event::default().push(
+                self.tags
+                    .iter()
+                    .fold(Push::default(), |push, tag| push.add_tag(tag)),
+            ),
+            ReleaseTrigger::Published => {
+                Event::default().release(Release::default().add_type(ReleaseType::Published))
+            }
+        };
+
+        GHWorkflow::new(self.name.clone())
+            .on(event)
+            .permissions(Permissions::default().contents(Level::Read))
+            .add_job("build", self.build_job())
+            .add_job("release", self.release_job())
+    }
+
+    /// The directory cargo writes the binaries of the matrix target to.
+    fn target_dir(&self) -> String {
+        let profile = match &self.build.cargo.profile {
+            Some(profile) if profile == "dev" => "debug",
+            Some(profile) => profile,
+            None if self.build.cargo.release => "release",
+            None => "debug",
+        };
+        format!("target/${{{{ matrix.target }}}}/{profile}")
+    }
+
+    /// The script that packages the binary of the matrix target.
+    fn package_script(&self) -> String {
+        let binary = &self.binary;
+        let include = self
+            .include
+            .iter()
+            .map(|path| format!(" {}", shell_quote(path)))
+            .collect::<String>();
+
+        format!(
+            r#"NAME="{binary}-${{GITHUB_REF_NAME}}-${{{{ matrix.target }}}}"
+mkdir -p "{DIST}" "$NAME"
+if [ "$RUNNER_OS" = "Windows" ]; then
+  cp "{dir}/{binary}.exe"{include} "$NAME/"
+  7z a "{DIST}/$NAME.zip" "$NAME"
+else
+  cp "{dir}/{binary}"{include} "$NAME/"
+  tar -czf "{DIST}/$NAME.tar.gz" "$NAME"
+fi"#,
+            dir = self.target_dir(),
+        )
+    }
+
+    /// Builds and packages the binary for every target.
+    fn build_job(&self) -> Job {
+        self.build
+            .job()
+            .add_step(
+                Step::new("Package")
+                    .run(self.package_script())
+                    .shell("bash"),
+            )
+            .add_step(
+                Step::new("Upload Archive")
+                    .uses("actions", "upload-artifact", "v4")
+                    .add_with(("name", format!("{ARTIFACT_PREFIX}${{{{ matrix.target }}}}")))
+                    .add_with(("path", format!("{DIST}/*")))
+                    .add_with(("if-no-files-found", "error")),
+            )
+    }
+
+    /// Collects the archives, checksums them and uploads them to the
+    /// release of the tag.
+    fn release_job(&self) -> Job {
+        let mut permissions = Permissions::default().contents(Level::Write);
+        if self.attest {
+            permissions = permissions
+                .id_token(Level::Write)
+                .attestations(Level::Write);
+        }
+
+        let mut job = Job::new("Release")
+            .add_needs("build")
+            .permissions(permissions)
+            .add_step(
+                Step::new("Download Archives")
+                    .uses("actions", "download-artifact", "v4")
+                    .add_with(("pattern", format!("{ARTIFACT_PREFIX}*")))
+                    .add_with(("path", DIST))
+                    .add_with(Input::default().add("merge-multiple", true)),
+            )
+            .add_step(
+                Step::new("Generate Checksums")
+                    .run("sha256sum * > SHA256SUMS")
+                    .working_directory(DIST),
+            );
+
+        if self.attest {
+            job = job.add_step(
+                Step::new("Attest Build Provenance")
+                    .uses("actions", "attest-build-provenance", "v2")
+                    .add_with(("subject-path", format!("{DIST}/*"))),
+            );
+        }
+
+        job.add_step(
+            Step::new("Upload Release Assets")
+                .uses("softprops", "action-gh-release", "v2")
+                .add_with(("files", format!("{DIST}/*"))),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_snapshot;
+
+    use super::*;
+
+    #[test]
+    fn test_binary_release() {
+        let release = BinaryRelease::new(
+            "tailcall",
+            [
+                Target::X86_64_UNKNOWN_LINUX_GNU,
+                Target::AARCH64_UNKNOWN_LINUX_MUSL,
+                Target::AARCH64_APPLE_DARWIN,
+                Target::X86_64_PC_WINDOWS_MSVC,
+            ],
+        )
+        .add_include("README.md")
+        .add_include("LICENSE")
+        .attest(true);
+
+        assert_snapshot!(release.to_workflow().to_string().unwrap());
+    }
+
+    #[test]
+    fn test_published_trigger() {
+        let workflow = BinaryRelease::new("tailcall", [Target::X86_64_UNKNOWN_LINUX_GNU])
+            .trigger(ReleaseTrigger::Published)
+            .to_workflow();
+        let on = workflow.on.unwrap();
+
+        assert!(on.push.is_none());
+        assert!(on.release.is_some());
+    }
+
+    #[test]
+    fn test_package_script_quotes_includes() {
+        let script = BinaryRelease::new("tailcall", [Target::X86_64_UNKNOWN_LINUX_GNU])
+            .add_include("docs/My Guide.md")
+            .add_include("it's.txt")
+            .package_script();
+
+        assert!(script.contains(r#"/tailcall" 'docs/My Guide.md' 'it'\''s.txt' "$NAME/""#));
+    }
+}
@@ -1,6 +1,8 @@
+mod binary_release;
 mod release;
 mod standard;
 
+pub use binary_release::*;
 pub use gh_workflow::*;
 pub use release::*;
 pub use standard::*;
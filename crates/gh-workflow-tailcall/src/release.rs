@@ -4,8 +4,11 @@
 
 use gh_workflow::error::Result;
 use gh_workflow::generate::Generate;
+use gh_workflow::workspace::Workspace;
 use gh_workflow::{Workflow as GHWorkflow, *};
 
+use crate::StandardWorkflow;
+
 /// Generates the release-drafter workflow that drafts a release on every
 /// push to main and auto-labels pull requests.
 ///
@@ -13,49 +16,70 @@ use gh_workflow::{Workflow as GHWorkflow, *};
 /// Returns an error if the workflow file cannot be generated or is outdated
 /// on CI.
 pub fn generate_release_drafter() -> Result<()> {
-    let workflow = GHWorkflow::new("Release Drafter")
-        .on(Event {
-            push: Some(Push::default().add_branch("main")),
-            pull_request_target: Some(
-                PullRequestTarget::default()
-                    .add_type(PullRequestType::Opened)
-                    .add_type(PullRequestType::Reopened)
-                    .add_type(PullRequestType::Synchronize)
-                    .add_type(PullRequestType::Labeled)
-                    .add_type(PullRequestType::Unlabeled)
-                    .add_type(PullRequestType::Closed)
-                    .add_branch("main"),
-            ),
-            ..Event::default()
-        })
-        .permissions(
-            Permissions::default()
-                .contents(Level::Write)
-                .pull_requests(Level::Write),
-        )
-        .add_job(
-            "update_release_draft",
-            Job::new("update_release_draft")
-                .add_step(
-                    Step::new("Auto Labeler")
-                        .uses("release-drafter", "release-drafter/autolabeler", "v7")
-                        .if_condition(Expression::new(
-                            "github.event_name == 'pull_request_target'",
-                        ))
-                        .add_env(("GITHUB_TOKEN", "${{ secrets.GITHUB_TOKEN }}"))
-                        .add_with(("config-name", "release-drafter.yml")),
-                )
-                .add_step(
-                    Step::new("Release Drafter")
-                        .uses("release-drafter", "release-drafter", "v7")
-                        .add_env(("GITHUB_TOKEN", "${{ secrets.GITHUB_TOKEN }}"))
-                        .add_with(("config-name", "release-drafter.yml")),
-                ),
-        );
+    StandardWorkflow::default().generate_release_drafter()
+}
 
-    Generate::new(workflow)
-        .name("release-drafter.yml")
-        .generate()
+impl StandardWorkflow {
+    /// Generates the release-drafter workflow for the branches of the
+    /// workflow.
+    ///
+    /// # Errors
+    /// Returns an error if the workflow file cannot be generated or is
+    /// outdated on CI.
+    pub fn generate_release_drafter(&self) -> Result<()> {
+        Generate::new(self.to_release_drafter_workflow())
+            .name("release-drafter.yml")
+            .validate_schema(true)
+            .generate()
+    }
+
+    /// Converts the release drafter into a Github workflow. It drafts a
+    /// release on every push to the branches of the workflow.
+    pub fn to_release_drafter_workflow(&self) -> GHWorkflow {
+        let pull_request_target = PullRequestTarget {
+            branches: self.branches.clone(),
+            ..PullRequestTarget::default()
+        };
+
+        GHWorkflow::new("Release Drafter")
+            .on(Event {
+                push: Some(Push { branches: self.branches.clone(), ..Push::default() }),
+                pull_request_target: Some(
+                    pull_request_target
+                        .add_type(PullRequestType::Opened)
+                        .add_type(PullRequestType::Reopened)
+                        .add_type(PullRequestType::Synchronize)
+                        .add_type(PullRequestType::Labeled)
+                        .add_type(PullRequestType::Unlabeled)
+                        .add_type(PullRequestType::Closed),
+                ),
+                ..Event::default()
+            })
+            .permissions(
+                Permissions::default()
+                    .contents(Level::Write)
+                    .pull_requests(Level::Write),
+            )
+            .add_job(
+                "update_release_draft",
+                Job::new("update_release_draft")
+                    .add_step(
+                        Step::new("Auto Labeler")
+                            .uses("release-drafter", "release-drafter/autolabeler", "v7")
+                            .if_condition(Expression::new(
+                                "github.event_name == 'pull_request_target'",
+                            ))
+                            .add_env(("GITHUB_TOKEN", "${{ secrets.GITHUB_TOKEN }}"))
+                            .add_with(("config-name", "release-drafter.yml")),
+                    )
+                    .add_step(
+                        Step::new("Release Drafter")
+                            .uses("release-drafter", "release-drafter", "v7")
+                            .add_env(("GITHUB_TOKEN", "${{ secrets.GITHUB_TOKEN }}"))
+                            .add_with(("config-name", "release-drafter.yml")),
+                    ),
+            )
+    }
 }
 
 /// Generates the release publish workflow that publishes all workspace
@@ -66,39 +90,42 @@ pub fn generate_release_drafter() -> Result<()> {
 /// Returns an error if the workflow file cannot be generated or is outdated
 /// on CI.
 pub fn generate_release_publish() -> Result<()> {
+    let publish_steps = Workspace::load()?
+        .publish_commands()?
+        .into_iter()
+        .map(|cargo| {
+            Step::<Run>::from(cargo.add_args("--allow-dirty")).add_env((
+                "CARGO_REGISTRY_TOKEN",
+                "${{ secrets.CARGO_REGISTRY_TOKEN }}",
+            ))
+        });
+
+    let mut job = Job::new("publish")
+        .add_step(Step::checkout().name("Checkout Code"))
+        .add_step(
+            Step::new("Setup Rust Toolchain")
+                .uses("actions-rust-lang", "setup-rust-toolchain", "v1")
+                .add_with(("toolchain", "stable")),
+        )
+        .add_step(Step::new("Set Version from Tag").run(
+            r#"VERSION="${GITHUB_REF_NAME#v}"
+cargo install cargo-edit --locked
+cargo set-version --workspace "$VERSION""#,
+        ));
+    for step in publish_steps {
+        job = job.add_step(step);
+    }
+
     let workflow = GHWorkflow::new("Release Publish")
         .on(Event {
             release: Some(Release::default().add_type(ReleaseType::Published)),
             ..Event::default()
         })
         .permissions(Permissions::default().contents(Level::Read))
-        .add_job(
-            "publish",
-            Job::new("publish")
-                .add_step(Step::checkout().name("Checkout Code"))
-                .add_step(
-                    Step::new("Setup Rust Toolchain")
-                        .uses("actions-rust-lang", "setup-rust-toolchain", "v1")
-                        .add_with(("toolchain", "stable")),
-                )
-                .add_step(Step::new("Set Version from Tag").run(
-                    r#"VERSION="${GITHUB_REF_NAME#v}"
-cargo install cargo-edit --locked
-cargo set-version --workspace "$VERSION""#,
-                ))
-                .add_step(
-                    Step::new("Publish to Crates.io")
-                        .run(
-                            r#"cargo publish -p gh-workflow-macros --allow-dirty
-cargo publish -p gh-workflow --allow-dirty
-cargo publish -p gh-workflow-tailcall --allow-dirty"#,
-                        )
-                        .add_env((
-                            "CARGO_REGISTRY_TOKEN",
-                            "${{ secrets.CARGO_REGISTRY_TOKEN }}",
-                        )),
-                ),
-        );
+        .add_job("publish", job);
 
-    Generate::new(workflow).name("release.yml").generate()
+    Generate::new(workflow)
+        .name("release.yml")
+        .validate_schema(true)
+        .generate()
 }
@@ -5,6 +5,10 @@
 //! by using feature flags to enable or disable features that you want in your
 //! workflow. Based on the features enabled or disabled a workflow is generated.
 
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+use cache::RustCache;
 use derive_setters::Setters;
 use generate::Generate;
 use gh_workflow::error::Result;
@@ -39,6 +43,44 @@ pub struct StandardWorkflow {
 
     /// The test runner to use for running tests
     pub test_runner: TestRunner,
+
+    /// Branches that trigger the workflow on pushes and pull requests.
+    pub branches: Vec<String>,
+
+    /// Tag patterns that trigger the workflow when pushed.
+    pub tags: Vec<String>,
+
+    /// Only triggers the workflow when these paths change. Every change
+    /// triggers it when empty.
+    pub paths: Vec<String>,
+
+    /// Runs the CI workflow on the checks requested by a merge queue.
+    pub merge_group: bool,
+
+    /// Allows running the workflows manually.
+    pub workflow_dispatch: bool,
+
+    #[setters(skip)]
+    hooks: Hooks,
+}
+
+type EventHook = Arc<dyn Fn(Event) -> Event + Send + Sync>;
+type JobHook = Arc<dyn Fn(&str, Job) -> Job + Send + Sync>;
+
+/// Callbacks that adjust the generated values.
+#[derive(Clone, Default)]
+struct Hooks {
+    event: Option<EventHook>,
+    job: Option<JobHook>,
+}
+
+impl Debug for Hooks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Hooks")
+            .field("event", &self.event.is_some())
+            .field("job", &self.job.is_some())
+            .finish()
+    }
 }
 
 impl Default for StandardWorkflow {
@@ -49,6 +91,12 @@ impl Default for StandardWorkflow {
             auto_fix: false,
             setup: Vec::new(),
             test_runner: TestRunner::default(),
+            branches: vec!["main".to_string()],
+            tags: vec!["v*".to_string()],
+            paths: Vec::new(),
+            merge_group: false,
+            workflow_dispatch: false,
+            hooks: Hooks::default(),
         }
     }
 }
@@ -84,14 +132,74 @@ impl StandardWorkflow {
         self.setup.push(step.into());
         self
     }
+
+    /// Adds a branch that triggers the workflow, on top of `main`. Use
+    /// `branches` to replace it.
+    pub fn add_branch<T: ToString>(mut self, branch: T) -> Self {
+        self.branches.push(branch.to_string());
+        self
+    }
+
+    /// Adds a tag pattern that triggers the workflow when pushed.
+    pub fn add_tag<T: ToString>(mut self, tag: T) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    /// Adds a path that triggers the workflow when it changes.
+    pub fn add_path<T: ToString>(mut self, path: T) -> Self {
+        self.paths.push(path.to_string());
+        self
+    }
+
+    /// Adjusts the `on` of the CI and autofix workflows. The release drafter
+    /// keeps its own triggers.
+    ///
+    /// # Example
+    /// ```
+    /// use gh_workflow_tailcall::*;
+    ///
+    /// let workflow = StandardWorkflow::default().customize_event(|event| {
+    ///     event.schedule(vec![Schedule::new("0 0 * * *")])
+    /// });
+    /// let on = workflow.to_ci_workflow().on.unwrap();
+    /// assert_eq!(on.schedule.unwrap()[0].cron, "0 0 * * *");
+    /// ```
+    pub fn customize_event<F>(mut self, f: F) -> Self
+    where
+        F: Fn(Event) -> Event + Send + Sync + 'static,
+    {
+        self.hooks.event = Some(Arc::new(f));
+        self
+    }
+
+    /// Adjusts every job of the CI and autofix workflows, it's called with
+    /// the id of the job, for eg: `build` or `lint`.
+    pub fn customize_job<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str, Job) -> Job + Send + Sync + 'static,
+    {
+        self.hooks.job = Some(Arc::new(f));
+        self
+    }
+
+    fn customize(&self, id: &str, job: Job) -> Job {
+        match &self.hooks.job {
+            Some(hook) => hook(id, job),
+            None => job,
+        }
+    }
 }
 
 impl StandardWorkflow {
     /// Generates and tests the workflow file.
     pub fn generate(self) -> Result<()> {
-        self.to_ci_workflow().generate()?;
+        Generate::new(self.to_ci_workflow())
+            .validate_schema(true)
+            .generate()?;
         Generate::new(self.to_autofix_workflow())
             .name("autofix.yml")
+            .validate_schema(true)
             .generate()?;
         Ok(())
     }
@@ -101,17 +209,17 @@ impl StandardWorkflow {
         // NOTE: The workflow name needs to by `autofix.ci`
         GHWorkflow::new("autofix.ci")
             .add_env(self.workflow_flags())
-            .on(self.workflow_event())
-            .add_job("lint", self.lint_job(true))
+            .on(self.workflow_event(false))
+            .add_job("lint", self.customize("lint", self.lint_job(true)))
     }
 
     /// Converts the workflow into a Github workflow.
     pub fn to_ci_workflow(&self) -> GHWorkflow {
         GHWorkflow::new(self.name.clone())
             .add_env(self.workflow_flags())
-            .on(self.workflow_event())
-            .add_job("build", self.test_job())
-            .add_job("lint", self.lint_job(false))
+            .on(self.workflow_event(true))
+            .add_job("build", self.customize("build", self.test_job()))
+            .add_job("lint", self.customize("lint", self.lint_job(false)))
     }
 
     fn lint_job(&self, auto_fix: bool) -> Job {
@@ -139,7 +247,11 @@ impl StandardWorkflow {
             clippy_step = clippy_step.add_args("--fix").add_args("--allow-dirty");
         }
 
-        clippy_step = clippy_step.add_args("--all-features --workspace -- -D warnings");
+        clippy_step = clippy_step
+            .all_features(true)
+            .workspace(true)
+            .add_trailing_arg("-D")
+            .add_trailing_arg("warnings");
 
         job = job
             .add_step(
@@ -177,40 +289,113 @@ impl StandardWorkflow {
             );
         }
         job = job
-            .add_step(
-                Step::new("Cache Rust dependencies")
-                    .uses("Swatinem", "rust-cache", "v2")
-                    .add_with(("cache-all-crates", "true")),
-            )
+            .add_step(RustCache::default().cache_all_crates(true))
             .add_step(match self.test_runner {
                 TestRunner::Cargo => Cargo::new("test")
-                    .args("--all-features --workspace")
+                    .all_features(true)
+                    .workspace(true)
                     .name("Cargo Test"),
                 TestRunner::Nextest => Cargo::new("nextest")
-                    .args("run --all-features --workspace")
+                    .args("run")
+                    .all_features(true)
+                    .workspace(true)
                     .name("Cargo Nextest"),
             });
 
         if self.benchmarks {
-            job = job.add_step(Cargo::new("bench").args("--workspace").name("Cargo Bench"));
+            job = job.add_step(Cargo::new("bench").workspace(true).name("Cargo Bench"));
         }
 
         job
     }
 
-    fn workflow_event(&self) -> Event {
-        Event::default()
-            .push(Push::default().add_branch("main").add_tag("v*"))
-            .pull_request(
-                PullRequest::default()
-                    .add_type(PullRequestType::Opened)
-                    .add_type(PullRequestType::Synchronize)
-                    .add_type(PullRequestType::Reopened)
-                    .add_branch("main"),
-            )
+    /// The triggers of the workflows. The autofix workflow doesn't run in
+    /// merge queues, it can't push to their temporary branches.
+    fn workflow_event(&self, merge_group: bool) -> Event {
+        let push = Push {
+            branches: self.branches.clone(),
+            tags: self.tags.clone(),
+            paths: self.paths.clone(),
+            ..Push::default()
+        };
+        let pull_request = PullRequest {
+            branches: self.branches.clone(),
+            paths: self.paths.clone(),
+            ..PullRequest::default()
+        }
+        .add_type(PullRequestType::Opened)
+        .add_type(PullRequestType::Synchronize)
+        .add_type(PullRequestType::Reopened);
+
+        let mut event = Event::default().push(push).pull_request(pull_request);
+
+        if merge_group && self.merge_group {
+            event =
+                event.merge_group(MergeGroup::default().add_type(MergeGroupType::ChecksRequested));
+        }
+
+        if self.workflow_dispatch {
+            event = event.workflow_dispatch(WorkflowDispatch::default());
+        }
+
+        match &self.hooks.event {
+            Some(hook) => hook(event),
+            None => event,
+        }
     }
 
     fn workflow_flags(&self) -> RustFlags {
         RustFlags::deny("warnings")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_workflow_event() {
+        let workflow = StandardWorkflow::default()
+            .branches(vec!["master".to_string()])
+            .add_branch("release/*")
+            .tags(Vec::<String>::new())
+            .add_path("src/**")
+            .merge_group(true)
+            .workflow_dispatch(true);
+
+        let event = workflow.to_ci_workflow().on.unwrap();
+        let push = event.push.unwrap();
+        assert_eq!(push.branches, ["master", "release/*"]);
+        assert!(push.tags.is_empty());
+        assert_eq!(push.paths, ["src/**"]);
+        assert_eq!(
+            event.pull_request.unwrap().branches,
+            ["master", "release/*"]
+        );
+        assert!(event.merge_group.is_some());
+        assert!(event.workflow_dispatch.is_some());
+
+        // The autofix workflow can't push to the branches of a merge queue.
+        let event = workflow.to_autofix_workflow().on.unwrap();
+        assert!(event.merge_group.is_none());
+    }
+
+    #[test]
+    fn test_hooks() {
+        let workflow = StandardWorkflow::default()
+            .customize_event(|event| event.schedule(vec![Schedule::new("0 0 * * *")]))
+            .customize_job(|id, job| match id {
+                "build" => job.timeout_minutes(30u32),
+                _ => job,
+            });
+
+        let ci = workflow.to_ci_workflow();
+        assert_eq!(ci.on.unwrap().schedule.unwrap()[0].cron, "0 0 * * *");
+
+        let jobs = ci.jobs.unwrap();
+        assert_eq!(jobs.get("build").unwrap().timeout_minutes, Some(30));
+        assert_eq!(jobs.get("lint").unwrap().timeout_minutes, None);
+    }
+}
@@ -1,9 +1,13 @@
 //!
-//! Artifact types for GitHub workflow job outputs and inputs.
+//! Artifact types for GitHub workflow job outputs and inputs. Artifacts are
+//! not a GitHub Actions key; when the workflow is generated they are lowered
+//! into `actions/upload-artifact` and `actions/download-artifact` steps.
 
 use derive_setters::Setters;
 use serde::{Deserialize, Serialize};
 
+use crate::{Input, Step, Use};
+
 /// Represents artifacts produced by jobs.
 #[derive(Debug, Setters, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
@@ -13,11 +17,25 @@ pub struct Artifacts {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub upload: Option<Vec<Artifact>>,
 
-    /// Artifacts to download before the job.
+    /// Artifacts to download before the job, after its leading checkouts.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub download: Option<Vec<Artifact>>,
 }
 
+impl Artifacts {
+    /// Adds an artifact to upload.
+    pub fn add_upload(mut self, artifact: Artifact) -> Self {
+        self.upload.get_or_insert_with(Vec::new).push(artifact);
+        self
+    }
+
+    /// Adds an artifact to download.
+    pub fn add_download(mut self, artifact: Artifact) -> Self {
+        self.download.get_or_insert_with(Vec::new).push(artifact);
+        self
+    }
+}
+
 /// Represents an artifact produced by a job.
 #[derive(Debug, Setters, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
@@ -26,10 +44,82 @@ pub struct Artifact {
     /// The name of the artifact.
     pub name: String,
 
-    /// The path to the artifact.
+    /// The path to upload from, or download to.
     pub path: String,
 
-    /// The number of days to retain the artifact.
+    /// The number of days to retain the artifact. Only used for uploads.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub retention_days: Option<u32>,
+
+    /// What to do when no files match `path`. Only used for uploads.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub if_no_files_found: Option<IfNoFilesFound>,
+
+    /// Downloads every artifact whose name matches `name`, used as a glob
+    /// pattern, into the same directory. Only used for downloads.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub merge_multiple: Option<bool>,
+}
+
+/// The behaviour of an upload when no files match its path.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum IfNoFilesFound {
+    /// Output a warning but do not fail the action.
+    Warn,
+    /// Fail the action with an error message.
+    Error,
+    /// Do not output any warnings or errors.
+    Ignore,
+}
+
+impl Artifact {
+    /// Creates a new `Artifact` with the given name and path.
+    pub fn new<N: ToString, P: ToString>(name: N, path: P) -> Self {
+        Self {
+            name: name.to_string(),
+            path: path.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Creates an `actions/upload-artifact` step for this artifact.
+    pub fn upload_step(&self) -> Step<Use> {
+        let mut step = Step::new(format!("Upload {}", self.name))
+            .uses("actions", "upload-artifact", "v4")
+            .add_with(("name", &self.name))
+            .add_with(("path", &self.path));
+
+        if let Some(retention_days) = self.retention_days {
+            step = step.add_with(Input::default().add("retention-days", retention_days));
+        }
+
+        if let Some(if_no_files_found) = self.if_no_files_found {
+            let value = match if_no_files_found {
+                IfNoFilesFound::Warn => "warn",
+                IfNoFilesFound::Error => "error",
+                IfNoFilesFound::Ignore => "ignore",
+            };
+            step = step.add_with(("if-no-files-found", value));
+        }
+
+        step
+    }
+
+    /// Creates an `actions/download-artifact` step for this artifact.
+    pub fn download_step(&self) -> Step<Use> {
+        let step =
+            Step::new(format!("Download {}", self.name)).uses("actions", "download-artifact", "v4");
+
+        // `merge-multiple` only applies to the artifacts selected by a
+        // `pattern`, a single artifact selected by `name` is never merged.
+        if self.merge_multiple == Some(true) {
+            step.add_with(("pattern", &self.name))
+                .add_with(("path", &self.path))
+                .add_with(Input::default().add("merge-multiple", true))
+        } else {
+            step.add_with(("name", &self.name))
+                .add_with(("path", &self.path))
+        }
+    }
 }
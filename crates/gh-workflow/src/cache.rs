@@ -0,0 +1,406 @@
+//! The typed versions of the `cache` and `rust-cache` actions in a GitHub
+//! workflow.
+//! Docs: <https://github.com/actions/cache> and <https://github.com/Swatinem/rust-cache>
+
+use std::fmt::{Display, Formatter};
+
+use derive_setters::Setters;
+
+use crate::ctx::Context;
+use crate::{private, Expression, Input, Step, StepType, StepValue, Use};
+
+/// A single part of a [`CacheKey`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyPart {
+    /// A fixed string, for eg: `cargo`.
+    Literal(String),
+    /// The operating system of the runner.
+    RunnerOs,
+    /// The architecture of the runner.
+    RunnerArch,
+    /// The hash of the files matching the patterns.
+    HashFiles(Vec<String>),
+    /// Any other expression, for eg: `matrix.target`.
+    Expression(String),
+}
+
+impl Display for KeyPart {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Literal(value) => write!(f, "{value}"),
+            Self::RunnerOs => write!(f, "${{{{ runner.os }}}}"),
+            Self::RunnerArch => write!(f, "${{{{ runner.arch }}}}"),
+            Self::HashFiles(patterns) => {
+                let patterns = patterns
+                    .iter()
+                    // Expressions escape `'` in strings by doubling it.
+                    .map(|p| format!("'{}'", p.replace('\'', "''")))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "${{{{ hashFiles({patterns}) }}}}")
+            }
+            Self::Expression(expr) => write!(f, "${{{{ {expr} }}}}"),
+        }
+    }
+}
+
+/// A cache key made of parts that are joined with `-`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CacheKey(Vec<KeyPart>);
+
+impl CacheKey {
+    /// Creates a new `CacheKey` that starts with the given prefix.
+    pub fn new<T: ToString>(prefix: T) -> Self {
+        Self(vec![KeyPart::Literal(prefix.to_string())])
+    }
+
+    /// Adds a part to the key.
+    pub fn add_part(mut self, part: KeyPart) -> Self {
+        self.0.push(part);
+        self
+    }
+
+    /// Adds the operating system of the runner to the key.
+    pub fn runner_os(self) -> Self {
+        self.add_part(KeyPart::RunnerOs)
+    }
+
+    /// Adds the architecture of the runner to the key.
+    pub fn runner_arch(self) -> Self {
+        self.add_part(KeyPart::RunnerArch)
+    }
+
+    /// Adds the hash of the files matching `pattern` to the key.
+    pub fn hash_files<T: ToString>(self, pattern: T) -> Self {
+        self.add_part(KeyPart::HashFiles(vec![pattern.to_string()]))
+    }
+
+    /// Adds the hash of every `Cargo.lock` to the key.
+    pub fn lockfile(self) -> Self {
+        self.hash_files("**/Cargo.lock")
+    }
+
+    /// Returns every proper prefix of the key, longest first, each with a
+    /// trailing `-` so that it can be used as a restore key.
+    pub fn prefixes(&self) -> Vec<String> {
+        (1..self.0.len())
+            .rev()
+            .map(|len| format!("{}-", Self(self.0[..len].to_vec())))
+            .collect()
+    }
+}
+
+impl Display for CacheKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let parts = self.0.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        write!(f, "{}", parts.join("-"))
+    }
+}
+
+/// Which of the cache actions a [`Cache`] step uses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CacheAction {
+    /// Restores the cache and saves it at the end of the job.
+    #[default]
+    Cache,
+    /// Only restores the cache.
+    Restore,
+    /// Only saves the cache.
+    Save,
+}
+
+///
+/// A Rust representation for the inputs of the cache action.
+/// More information can be found [here](https://github.com/actions/cache/blob/main/action.yml).
+/// NOTE: The public API should be close to the original action as much as
+/// possible.
+#[derive(Clone, Setters)]
+#[setters(strip_option, into)]
+pub struct Cache {
+    /// The id of the step, needed to refer to its outputs. It must be unique
+    /// in the job.
+    pub id: Option<String>,
+    pub action: CacheAction,
+    pub path: Vec<String>,
+    pub key: CacheKey,
+    pub restore_keys: Vec<String>,
+    pub lookup_only: Option<bool>,
+    pub fail_on_cache_miss: Option<bool>,
+    pub enable_cross_os_archive: Option<bool>,
+}
+
+impl Cache {
+    /// Creates a new `Cache` step with the given key.
+    pub fn new(key: CacheKey) -> Self {
+        Self {
+            id: None,
+            action: CacheAction::default(),
+            path: Vec::new(),
+            key,
+            restore_keys: Vec::new(),
+            lookup_only: None,
+            fail_on_cache_miss: None,
+            enable_cross_os_archive: None,
+        }
+    }
+
+    /// Adds a path to cache.
+    pub fn add_path<T: ToString>(mut self, path: T) -> Self {
+        self.path.push(path.to_string());
+        self
+    }
+
+    /// Adds a restore key.
+    pub fn add_restore_key<T: ToString>(mut self, key: T) -> Self {
+        self.restore_keys.push(key.to_string());
+        self
+    }
+
+    /// Uses every prefix of the key as a restore key.
+    pub fn restore_prefixes(mut self) -> Self {
+        self.restore_keys.extend(self.key.prefixes());
+        self
+    }
+
+    /// Whether an exact match was found for the key, `None` when the step
+    /// has no `id`.
+    pub fn cache_hit(&self) -> Option<Context<bool>> {
+        let id = self.id.as_ref()?;
+        Some(Context::steps().get(id).outputs().get("cache-hit"))
+    }
+}
+
+impl From<Cache> for Step<Use> {
+    fn from(value: Cache) -> Self {
+        let (name, repo) = match value.action {
+            CacheAction::Cache => ("Cache", "cache"),
+            CacheAction::Restore => ("Restore Cache", "cache/restore"),
+            CacheAction::Save => ("Save Cache", "cache/save"),
+        };
+        let mut step = Step::new(name).uses("actions", repo, "v4");
+        if let Some(id) = value.id {
+            step = step.id(id);
+        }
+
+        let mut input = Input::default()
+            .add("path", value.path.join("\n"))
+            .add("key", value.key.to_string());
+
+        if value.action != CacheAction::Save {
+            if !value.restore_keys.is_empty() {
+                input = input.add("restore-keys", value.restore_keys.join("\n"));
+            }
+
+            if let Some(lookup_only) = value.lookup_only {
+                input = input.add("lookup-only", lookup_only);
+            }
+
+            if let Some(fail_on_cache_miss) = value.fail_on_cache_miss {
+                input = input.add("fail-on-cache-miss", fail_on_cache_miss);
+            }
+        }
+
+        if let Some(enable_cross_os_archive) = value.enable_cross_os_archive {
+            input = input.add("enableCrossOsArchive", enable_cross_os_archive);
+        }
+
+        step.with(input)
+    }
+}
+
+impl StepType for Cache {
+    fn to_value(s: Step<Self>) -> StepValue {
+        let step: Step<Use> = s.marker.into();
+        StepValue::from(step)
+    }
+}
+
+impl private::Sealed for Cache {}
+
+///
+/// A Rust representation for the inputs of the rust-cache action.
+/// More information can be found [here](https://github.com/Swatinem/rust-cache/blob/master/action.yml).
+/// NOTE: The public API should be close to the original action as much as
+/// possible.
+#[derive(Clone, Default, Setters)]
+#[setters(strip_option, into)]
+pub struct RustCache {
+    /// The id of the step, needed to refer to its outputs. It must be unique
+    /// in the job.
+    pub id: Option<String>,
+    pub prefix_key: Option<String>,
+    pub shared_key: Option<String>,
+    pub key: Option<String>,
+    pub workspaces: Vec<String>,
+    pub cache_directories: Vec<String>,
+    pub cache_targets: Option<bool>,
+    pub cache_on_failure: Option<bool>,
+    pub cache_all_crates: Option<bool>,
+    pub save_if: Option<Expression>,
+    pub lookup_only: Option<bool>,
+}
+
+impl RustCache {
+    /// Adds a workspace, written as `path -> target-dir`.
+    pub fn add_workspace<T: ToString>(mut self, workspace: T) -> Self {
+        self.workspaces.push(workspace.to_string());
+        self
+    }
+
+    /// Adds an additional directory to cache.
+    pub fn add_cache_directory<T: ToString>(mut self, directory: T) -> Self {
+        self.cache_directories.push(directory.to_string());
+        self
+    }
+
+    /// Whether an exact match was found for the key, `None` when the step
+    /// has no `id`.
+    pub fn cache_hit(&self) -> Option<Context<bool>> {
+        let id = self.id.as_ref()?;
+        Some(Context::steps().get(id).outputs().get("cache-hit"))
+    }
+}
+
+impl From<RustCache> for Step<Use> {
+    fn from(value: RustCache) -> Self {
+        let mut step = Step::new("Cache Rust dependencies").uses("Swatinem", "rust-cache", "v2");
+        if let Some(id) = value.id {
+            step = step.id(id);
+        }
+
+        let mut input = Input::default();
+
+        if let Some(prefix_key) = value.prefix_key {
+            input = input.add("prefix-key", prefix_key);
+        }
+
+        if let Some(shared_key) = value.shared_key {
+            input = input.add("shared-key", shared_key);
+        }
+
+        if let Some(key) = value.key {
+            input = input.add("key", key);
+        }
+
+        if !value.workspaces.is_empty() {
+            input = input.add("workspaces", value.workspaces.join("\n"));
+        }
+
+        if !value.cache_directories.is_empty() {
+            input = input.add("cache-directories", value.cache_directories.join("\n"));
+        }
+
+        if let Some(cache_targets) = value.cache_targets {
+            input = input.add("cache-targets", cache_targets);
+        }
+
+        if let Some(cache_on_failure) = value.cache_on_failure {
+            input = input.add("cache-on-failure", cache_on_failure);
+        }
+
+        if let Some(cache_all_crates) = value.cache_all_crates {
+            input = input.add("cache-all-crates", cache_all_crates);
+        }
+
+        if let Some(save_if) = value.save_if {
+            input = input.add("save-if", save_if.0);
+        }
+
+        if let Some(lookup_only) = value.lookup_only {
+            input = input.add("lookup-only", lookup_only);
+        }
+
+        if !input.is_empty() {
+            step = step.with(input);
+        }
+        step
+    }
+}
+
+impl StepType for RustCache {
+    fn to_value(s: Step<Self>) -> StepValue {
+        let step: Step<Use> = s.marker.into();
+        StepValue::from(step)
+    }
+}
+
+impl private::Sealed for RustCache {}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_cache_key() {
+        let key = CacheKey::new("cargo").runner_os().lockfile();
+
+        assert_eq!(
+            key.to_string(),
+            "cargo-${{ runner.os }}-${{ hashFiles('**/Cargo.lock') }}"
+        );
+        assert_eq!(key.prefixes(), vec!["cargo-${{ runner.os }}-", "cargo-"]);
+    }
+
+    #[test]
+    fn test_cache_key_escapes_quotes() {
+        let key = CacheKey::new("cargo").hash_files("it's/**/Cargo.lock");
+
+        assert_eq!(
+            key.to_string(),
+            "cargo-${{ hashFiles('it''s/**/Cargo.lock') }}"
+        );
+    }
+
+    #[test]
+    fn test_cache_step() {
+        let cache = Cache::new(CacheKey::new("cargo").runner_os().lockfile())
+            .add_path("~/.cargo/registry")
+            .add_path("target")
+            .restore_prefixes();
+        assert!(cache.cache_hit().is_none());
+
+        let cache = cache.id("cache");
+        assert_eq!(
+            cache.cache_hit().unwrap().to_string(),
+            "${{ steps.cache.outputs.cache-hit }}"
+        );
+
+        let step: Step<Use> = cache.action(CacheAction::Restore).into();
+        let expected = Step::new("Restore Cache")
+            .uses("actions", "cache/restore", "v4")
+            .id("cache")
+            .with(
+                Input::default()
+                    .add("path", "~/.cargo/registry\ntarget")
+                    .add(
+                        "key",
+                        "cargo-${{ runner.os }}-${{ hashFiles('**/Cargo.lock') }}",
+                    )
+                    .add("restore-keys", "cargo-${{ runner.os }}-\ncargo-"),
+            );
+
+        assert_eq!(step, expected);
+    }
+
+    #[test]
+    fn test_rust_cache_step() {
+        let step: Step<Use> = RustCache::default()
+            .shared_key("build")
+            .cache_on_failure(true)
+            .save_if(Expression::new("${{ github.ref == 'refs/heads/main' }}"))
+            .into();
+
+        let expected = Step::new("Cache Rust dependencies")
+            .uses("Swatinem", "rust-cache", "v2")
+            .with(
+                Input::default()
+                    .add("shared-key", "build")
+                    .add("cache-on-failure", true)
+                    .add("save-if", "${{ github.ref == 'refs/heads/main' }}"),
+            );
+
+        assert_eq!(step, expected);
+    }
+}
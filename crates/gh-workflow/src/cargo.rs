@@ -1,6 +1,6 @@
 use derive_setters::Setters;
 
-use crate::toolchain::Version;
+use crate::toolchain::{Target, Version};
 use crate::{Run, Step};
 
 #[derive(Clone, Setters)]
@@ -18,9 +18,83 @@ pub struct Cargo {
     /// Toolchain to be used for example `+nightly`.
     pub toolchain: Option<Version>,
 
-    /// Arguments to be passed to the cargo command.
+    /// Arguments to be passed to the cargo command. They are written as-is,
+    /// right after the command and before the typed options.
     #[setters(skip)]
     pub args: Vec<String>,
+
+    /// Packages to build, passed as `-p`.
+    pub packages: Vec<String>,
+
+    /// Builds all the packages of the workspace, `--workspace`.
+    pub workspace: bool,
+
+    /// Packages excluded from the workspace, passed as `--exclude`.
+    pub exclude: Vec<String>,
+
+    /// Features to enable, passed as `--features`.
+    pub features: Vec<String>,
+
+    /// Enables all the features, `--all-features`.
+    pub all_features: bool,
+
+    /// Disables the default features, `--no-default-features`.
+    pub no_default_features: bool,
+
+    /// The target to build for, `--target`.
+    pub target: Option<Target>,
+
+    /// The profile to build with, `--profile`.
+    pub profile: Option<String>,
+
+    /// Builds with the release profile, `--release`.
+    pub release: bool,
+
+    /// Requires `Cargo.lock` to be up to date, `--locked`.
+    pub locked: bool,
+
+    /// Requires `Cargo.lock` and the cache to be up to date, `--frozen`.
+    pub frozen: bool,
+
+    /// Runs without accessing the network, `--offline`.
+    pub offline: bool,
+
+    /// The number of parallel jobs, `--jobs`.
+    pub jobs: Option<u32>,
+
+    /// The format of the diagnostics, `--message-format`.
+    pub message_format: Option<MessageFormat>,
+
+    /// Unstable flags passed as `-Z`, they require a nightly toolchain.
+    pub unstable: Vec<String>,
+
+    /// Arguments passed after `--`, to the tool run by cargo.
+    pub trailing_args: Vec<String>,
+}
+
+/// The format of the messages printed by cargo.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageFormat {
+    Human,
+    Short,
+    Json,
+    JsonDiagnosticShort,
+    JsonDiagnosticRenderedAnsi,
+    JsonRenderDiagnostics,
+}
+
+impl std::fmt::Display for MessageFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let val = match self {
+            Self::Human => "human",
+            Self::Short => "short",
+            Self::Json => "json",
+            Self::JsonDiagnosticShort => "json-diagnostic-short",
+            Self::JsonDiagnosticRenderedAnsi => "json-diagnostic-rendered-ansi",
+            Self::JsonRenderDiagnostics => "json-render-diagnostics",
+        };
+        write!(f, "{val}")
+    }
 }
 
 impl Cargo {
@@ -32,6 +106,22 @@ impl Cargo {
             name: Default::default(),
             toolchain: Default::default(),
             args: Default::default(),
+            packages: Default::default(),
+            workspace: Default::default(),
+            exclude: Default::default(),
+            features: Default::default(),
+            all_features: Default::default(),
+            no_default_features: Default::default(),
+            target: Default::default(),
+            profile: Default::default(),
+            release: Default::default(),
+            locked: Default::default(),
+            frozen: Default::default(),
+            offline: Default::default(),
+            jobs: Default::default(),
+            message_format: Default::default(),
+            unstable: Default::default(),
+            trailing_args: Default::default(),
         }
     }
 
@@ -58,13 +148,117 @@ impl Cargo {
         );
         self
     }
+
+    /// Adds a package to build.
+    pub fn add_package<T: ToString>(mut self, package: T) -> Self {
+        self.packages.push(package.to_string());
+        self
+    }
+
+    /// Adds a package to exclude from the workspace.
+    pub fn add_exclude<T: ToString>(mut self, package: T) -> Self {
+        self.exclude.push(package.to_string());
+        self
+    }
+
+    /// Adds a feature to enable.
+    pub fn add_feature<T: ToString>(mut self, feature: T) -> Self {
+        self.features.push(feature.to_string());
+        self
+    }
+
+    /// Adds an unstable `-Z` flag.
+    pub fn add_unstable<T: ToString>(mut self, flag: T) -> Self {
+        self.unstable.push(flag.to_string());
+        self
+    }
+
+    /// Adds an argument to pass after `--`.
+    pub fn add_trailing_arg<T: ToString>(mut self, arg: T) -> Self {
+        self.trailing_args.push(arg.to_string());
+        self
+    }
+
+    /// The typed options, quoted for the shell.
+    pub(crate) fn options(&self) -> Vec<String> {
+        let mut options = Vec::new();
+        let mut flag = |condition: bool, name: &str| {
+            if condition {
+                options.push(name.to_string());
+            }
+        };
+
+        flag(self.workspace, "--workspace");
+        flag(self.all_features, "--all-features");
+        flag(self.no_default_features, "--no-default-features");
+        flag(self.release, "--release");
+        flag(self.locked, "--locked");
+        flag(self.frozen, "--frozen");
+        flag(self.offline, "--offline");
+
+        let mut option = |name: &str, value: String| {
+            options.push(name.to_string());
+            options.push(shell_quote(&value));
+        };
+
+        for package in &self.packages {
+            option("-p", package.clone());
+        }
+        for package in &self.exclude {
+            option("--exclude", package.clone());
+        }
+        if !self.features.is_empty() {
+            option("--features", self.features.join(","));
+        }
+        if let Some(target) = &self.target {
+            option("--target", target.to_string());
+        }
+        if let Some(profile) = &self.profile {
+            option("--profile", profile.clone());
+        }
+        if let Some(jobs) = self.jobs {
+            option("--jobs", jobs.to_string());
+        }
+        if let Some(message_format) = self.message_format {
+            option("--message-format", message_format.to_string());
+        }
+        for flag in &self.unstable {
+            option("-Z", flag.clone());
+        }
+
+        if !self.trailing_args.is_empty() {
+            options.push("--".to_string());
+            options.extend(self.trailing_args.iter().map(|arg| shell_quote(arg)));
+        }
+
+        options
+    }
+
+    /// Whether an option had to be quoted. The quoting is POSIX-only, so the
+    /// step must then run with `shell: bash`, which every GitHub-hosted
+    /// runner provides, instead of the default `pwsh` on Windows.
+    pub(crate) fn needs_posix_shell(&self) -> bool {
+        self.options().iter().any(|option| option.starts_with('\''))
+    }
+}
+
+/// Quotes `arg` for a POSIX shell when it contains characters the shell
+/// would interpret. It's not valid for `pwsh` or `cmd`, a step running a
+/// quoted argument must set `shell: bash`.
+pub fn shell_quote(arg: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "-_=+.,/:@%".contains(c);
+    if !arg.is_empty() && arg.chars().all(is_safe) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
 }
 
 impl From<Cargo> for Step<Run> {
     fn from(value: Cargo) -> Self {
         let mut command = vec!["cargo".to_string()];
 
-        if let Some(toolchain) = value.toolchain {
+        if let Some(toolchain) = &value.toolchain {
             command.push(format!("+{toolchain}"));
         }
 
@@ -74,13 +268,19 @@ impl From<Cargo> for Step<Run> {
         command.extend(
             value
                 .args
-                .into_iter()
+                .iter()
                 .map(|arg| arg.trim().to_string())
                 .filter(|arg| !arg.is_empty()),
         );
 
+        command.extend(value.options());
+
         let mut step = Step::new(format!("Cargo {}", value.command)).run(command.join(" "));
 
+        if value.needs_posix_shell() {
+            step = step.shell("bash");
+        }
+
         if let Some(id) = value.id {
             step = step.id(id);
         }
@@ -92,3 +292,75 @@ impl From<Cargo> for Step<Run> {
         step
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::StepValue;
+
+    fn run(cargo: Cargo) -> String {
+        let step: Step<Run> = cargo.into();
+        StepValue::from(step).run.unwrap()
+    }
+
+    fn shell(cargo: Cargo) -> Option<String> {
+        let step: Step<Run> = cargo.into();
+        StepValue::from(step).shell
+    }
+
+    #[test]
+    fn test_typed_options() {
+        let cargo = Cargo::new("clippy")
+            .nightly()
+            .workspace(true)
+            .add_exclude("xtask")
+            .all_features(true)
+            .target(Target::X86_64_UNKNOWN_LINUX_MUSL)
+            .locked(true)
+            .jobs(4u32)
+            .message_format(MessageFormat::Short)
+            .add_unstable("unstable-options")
+            .add_trailing_arg("-D")
+            .add_trailing_arg("warnings");
+
+        assert_eq!(
+            run(cargo),
+            "cargo +nightly clippy --workspace --all-features --locked --exclude xtask \
+             --target x86_64-unknown-linux-musl --jobs 4 --message-format short \
+             -Z unstable-options -- -D warnings"
+        );
+    }
+
+    #[test]
+    fn test_raw_args_come_first() {
+        let cargo = Cargo::new("nextest")
+            .args("run")
+            .add_package("gh-workflow")
+            .add_feature("serde")
+            .add_feature("yaml")
+            .release(true);
+
+        assert_eq!(
+            run(cargo),
+            "cargo nextest run --release -p gh-workflow --features serde,yaml"
+        );
+    }
+
+    #[test]
+    fn test_quoting() {
+        let cargo = Cargo::new("run")
+            .profile("release with space")
+            .add_trailing_arg("it's")
+            .add_trailing_arg("$HOME")
+            .add_trailing_arg("");
+
+        assert_eq!(
+            run(cargo.clone()),
+            "cargo run --profile 'release with space' -- 'it'\\''s' '$HOME' ''"
+        );
+        assert_eq!(shell(cargo), Some("bash".to_string()));
+        assert_eq!(shell(Cargo::new("test").add_trailing_arg("-v")), None);
+    }
+}
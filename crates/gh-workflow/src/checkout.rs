@@ -0,0 +1,202 @@
+//! The typed version of the `checkout` action in a GitHub workflow.
+//! Docs: <https://github.com/actions/checkout>
+
+use std::fmt::{Display, Formatter};
+
+use derive_setters::Setters;
+
+use crate::{private, Input, Step, StepType, StepValue, Use};
+
+/// The released major versions of the `checkout` action.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Version {
+    V4,
+    V5,
+    V6,
+    #[default]
+    V7,
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let val = match self {
+            Self::V4 => "v4",
+            Self::V5 => "v5",
+            Self::V6 => "v6",
+            Self::V7 => "v7",
+        };
+        write!(f, "{val}")
+    }
+}
+
+/// Whether submodules are checked out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Submodules {
+    Disabled,
+    Enabled,
+    Recursive,
+}
+
+impl From<bool> for Submodules {
+    fn from(value: bool) -> Self {
+        if value {
+            Self::Enabled
+        } else {
+            Self::Disabled
+        }
+    }
+}
+
+impl Display for Submodules {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let val = match self {
+            Self::Disabled => "false",
+            Self::Enabled => "true",
+            Self::Recursive => "recursive",
+        };
+        write!(f, "{val}")
+    }
+}
+
+///
+/// A Rust representation for the inputs of the checkout action.
+/// More information can be found [here](https://github.com/actions/checkout/blob/main/action.yml).
+/// NOTE: The public API should be close to the original action as much as
+/// possible.
+#[derive(Default, Clone, Setters)]
+#[setters(strip_option, into)]
+pub struct Checkout {
+    pub version: Version,
+    pub repository: Option<String>,
+    /// The branch, tag or SHA to checkout, passed as the `ref` input.
+    pub git_ref: Option<String>,
+    pub token: Option<String>,
+    pub fetch_depth: Option<u32>,
+    pub fetch_tags: Option<bool>,
+    pub submodules: Option<Submodules>,
+    pub lfs: Option<bool>,
+    pub sparse_checkout: Vec<String>,
+    pub persist_credentials: Option<bool>,
+    pub path: Option<String>,
+    pub clean: Option<bool>,
+}
+
+impl Checkout {
+    /// Adds a pattern to the `sparse-checkout` input.
+    pub fn add_sparse_checkout<T: ToString>(mut self, path: T) -> Self {
+        self.sparse_checkout.push(path.to_string());
+        self
+    }
+}
+
+impl From<Checkout> for Step<Use> {
+    fn from(value: Checkout) -> Self {
+        let mut step =
+            Step::new("Checkout Code").uses("actions", "checkout", value.version.to_string());
+
+        let mut input = Input::default();
+
+        if let Some(repository) = value.repository {
+            input = input.add("repository", repository);
+        }
+
+        if let Some(git_ref) = value.git_ref {
+            input = input.add("ref", git_ref);
+        }
+
+        if let Some(token) = value.token {
+            input = input.add("token", token);
+        }
+
+        if let Some(fetch_depth) = value.fetch_depth {
+            input = input.add("fetch-depth", fetch_depth);
+        }
+
+        if let Some(fetch_tags) = value.fetch_tags {
+            input = input.add("fetch-tags", fetch_tags);
+        }
+
+        if let Some(submodules) = value.submodules {
+            input = match submodules {
+                Submodules::Recursive => input.add("submodules", submodules.to_string()),
+                _ => input.add("submodules", submodules == Submodules::Enabled),
+            };
+        }
+
+        if let Some(lfs) = value.lfs {
+            input = input.add("lfs", lfs);
+        }
+
+        if !value.sparse_checkout.is_empty() {
+            input = input.add("sparse-checkout", value.sparse_checkout.join("\n"));
+        }
+
+        if let Some(persist_credentials) = value.persist_credentials {
+            input = input.add("persist-credentials", persist_credentials);
+        }
+
+        if let Some(path) = value.path {
+            input = input.add("path", path);
+        }
+
+        if let Some(clean) = value.clean {
+            input = input.add("clean", clean);
+        }
+
+        if !input.is_empty() {
+            step = step.with(input);
+        }
+        step
+    }
+}
+
+impl StepType for Checkout {
+    fn to_value(s: Step<Self>) -> StepValue {
+        let step: Step<Use> = s.marker.into();
+        StepValue::from(step)
+    }
+}
+
+impl private::Sealed for Checkout {}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_default_checkout() {
+        let step: Step<Use> = Checkout::default().into();
+        assert_eq!(
+            step,
+            Step::new("Checkout Code").uses("actions", "checkout", "v7")
+        );
+    }
+
+    #[test]
+    fn test_checkout_inputs() {
+        let step: Step<Use> = Checkout::default()
+            .version(Version::V4)
+            .git_ref("${{ github.head_ref }}")
+            .fetch_depth(0u32)
+            .submodules(Submodules::Recursive)
+            .add_sparse_checkout("src")
+            .add_sparse_checkout("Cargo.toml")
+            .persist_credentials(false)
+            .into();
+
+        let expected = Step::new("Checkout Code")
+            .uses("actions", "checkout", "v4")
+            .with(
+                Input::default()
+                    .add("ref", "${{ github.head_ref }}")
+                    .add("fetch-depth", 0)
+                    .add("submodules", "recursive")
+                    .add("sparse-checkout", "src\nCargo.toml")
+                    .add("persist-credentials", false),
+            );
+
+        assert_eq!(step, expected);
+    }
+}
@@ -1,7 +1,14 @@
 //! Container configuration types for GitHub workflow jobs.
 
+use std::fmt::{Display, Formatter};
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::time::Duration;
+
 use derive_setters::Setters;
-use serde::{Deserialize, Serialize};
+use indexmap::IndexMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
 
 use crate::env::Env;
 
@@ -36,6 +43,146 @@ pub struct Container {
     /// Hostname for the container.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hostname: Option<String>,
+
+    /// The health check of the container, rendered into `options`.
+    #[serde(skip)]
+    pub health_check: Option<HealthCheck>,
+
+    /// The docker network to connect the container to, rendered into
+    /// `options`.
+    #[serde(skip)]
+    pub network: Option<String>,
+
+    /// The number of CPUs the container can use, for eg: `1.5`, rendered
+    /// into `options`.
+    #[serde(skip)]
+    pub cpus: Option<String>,
+
+    /// The memory limit of the container, for eg: `512m`, rendered into
+    /// `options`.
+    #[serde(skip)]
+    pub memory: Option<String>,
+
+    /// Overrides the entrypoint of the image, rendered into `options`.
+    #[serde(skip)]
+    pub entrypoint: Option<String>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
+}
+
+impl Container {
+    /// Creates a new `Container` running the given image.
+    pub fn new<T: ToString>(image: T) -> Self {
+        Self { image: image.to_string(), ..Default::default() }
+    }
+
+    /// Adds a port to expose from the container.
+    pub fn add_port<P: Into<Port>>(mut self, port: P) -> Self {
+        self.ports.get_or_insert_with(Vec::new).push(port.into());
+        self
+    }
+
+    /// Adds a volume to mount in the container.
+    pub fn add_volume<V: Into<Volume>>(mut self, volume: V) -> Self {
+        self.volumes
+            .get_or_insert_with(Vec::new)
+            .push(volume.into());
+        self
+    }
+
+    /// Returns `options` with the typed docker options appended.
+    pub fn docker_options(&self) -> Option<String> {
+        let mut options: Vec<String> = self.options.iter().cloned().collect();
+
+        if let Some(health_check) = &self.health_check {
+            options.push(health_check.to_string());
+        }
+
+        if let Some(network) = &self.network {
+            options.push(format!("--network {network}"));
+        }
+
+        if let Some(cpus) = &self.cpus {
+            options.push(format!("--cpus {cpus}"));
+        }
+
+        if let Some(memory) = &self.memory {
+            options.push(format!("--memory {memory}"));
+        }
+
+        if let Some(entrypoint) = &self.entrypoint {
+            options.push(format!("--entrypoint {}", quote(entrypoint)));
+        }
+
+        (!options.is_empty()).then(|| options.join(" "))
+    }
+}
+
+/// Represents the health check of a container.
+#[derive(Debug, Setters, Clone, Default, PartialEq, Eq)]
+#[setters(strip_option, into)]
+pub struct HealthCheck {
+    /// The command that checks the health of the container.
+    pub cmd: String,
+
+    /// The time between two checks.
+    pub interval: Option<Duration>,
+
+    /// The time after which a check is considered to have failed.
+    pub timeout: Option<Duration>,
+
+    /// The number of consecutive failures after which the container is
+    /// unhealthy.
+    pub retries: Option<u32>,
+
+    /// The time the container is given to start before failures count.
+    pub start_period: Option<Duration>,
+}
+
+impl HealthCheck {
+    /// Creates a new `HealthCheck` running the given command.
+    pub fn new<T: ToString>(cmd: T) -> Self {
+        Self { cmd: cmd.to_string(), ..Default::default() }
+    }
+}
+
+impl std::fmt::Display for HealthCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "--health-cmd {}", quote(&self.cmd))?;
+        if let Some(interval) = self.interval {
+            write!(f, " --health-interval {}", duration(interval))?;
+        }
+        if let Some(timeout) = self.timeout {
+            write!(f, " --health-timeout {}", duration(timeout))?;
+        }
+        if let Some(retries) = self.retries {
+            write!(f, " --health-retries {retries}")?;
+        }
+        if let Some(start_period) = self.start_period {
+            write!(f, " --health-start-period {}", duration(start_period))?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats a duration the way docker parses it, for eg: `10s` or `500ms`.
+fn duration(duration: Duration) -> String {
+    if duration.subsec_millis() == 0 {
+        format!("{}s", duration.as_secs())
+    } else {
+        format!("{}ms", duration.as_millis())
+    }
+}
+
+/// Quotes a docker option value that contains whitespace or quotes.
+fn quote(value: &str) -> String {
+    if value.contains(|c: char| c.is_whitespace() || c == '"' || c == '\'') {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
 }
 
 /// Represents credentials for accessing a container.
@@ -50,40 +197,732 @@ pub struct Credentials {
     pub password: String,
 }
 
-/// Represents a network port.
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
-#[serde(untagged)]
+/// Represents a port exposed from a container. Strings are parsed with the
+/// docker syntax `[ip:][host_port:]container_port[/protocol]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Port {
-    /// A port specified by its number.
+    /// A container port, mapped to a random port on the host.
     Number(u16),
 
-    /// A port specified by its name.
+    /// A port mapping between the host and the container.
+    Mapping(PortMapping),
+
+    /// A port given as an expression, for eg: `${{ matrix.port }}`.
     Name(String),
 }
 
-/// Represents a volume configuration for containers.
-#[derive(Debug, Setters, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
-#[serde(rename_all = "kebab-case")]
+impl From<u16> for Port {
+    fn from(value: u16) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<PortMapping> for Port {
+    fn from(value: PortMapping) -> Self {
+        Self::Mapping(value)
+    }
+}
+
+impl FromStr for Port {
+    type Err = PortError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let mapping = spec.parse::<PortMapping>()?;
+        if mapping.host_ip.is_none() && mapping.host_port.is_none() && mapping.protocol.is_none() {
+            Ok(Self::Number(mapping.container_port))
+        } else {
+            Ok(Self::Mapping(mapping))
+        }
+    }
+}
+
+impl Display for Port {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Number(port) => write!(f, "{port}"),
+            Self::Mapping(mapping) => write!(f, "{mapping}"),
+            Self::Name(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+impl Serialize for Port {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Number(port) => serializer.serialize_u16(*port),
+            port => serializer.collect_str(port),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Port {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Number(u16),
+            String(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Number(port) => Ok(Self::Number(port)),
+            Raw::String(spec) if spec.contains("${{") => Ok(Self::Name(spec)),
+            Raw::String(spec) => spec.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// The transport protocol of a port mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    Tcp,
+    Udp,
+    Sctp,
+}
+
+impl Display for Protocol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp => write!(f, "tcp"),
+            Self::Udp => write!(f, "udp"),
+            Self::Sctp => write!(f, "sctp"),
+        }
+    }
+}
+
+/// Maps a port of the container to a port of the host.
+#[derive(Debug, Setters, Clone, PartialEq, Eq)]
+#[setters(strip_option, into)]
+pub struct PortMapping {
+    /// The host interface to bind to, all interfaces when unset.
+    pub host_ip: Option<IpAddr>,
+
+    /// The port on the host, a random free port when unset.
+    pub host_port: Option<u16>,
+
+    /// The port inside the container.
+    #[setters(skip)]
+    pub container_port: u16,
+
+    /// The protocol of the port, `tcp` when unset.
+    pub protocol: Option<Protocol>,
+}
+
+impl PortMapping {
+    /// Creates a new `PortMapping` exposing the given container port.
+    pub fn new(container_port: u16) -> Self {
+        Self {
+            host_ip: None,
+            host_port: None,
+            container_port,
+            protocol: None,
+        }
+    }
+}
+
+impl FromStr for PortMapping {
+    type Err = PortError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let error = |reason: PortErrorReason| PortError { spec: spec.to_string(), reason };
+
+        if spec.trim().is_empty() {
+            return Err(error(PortErrorReason::Empty));
+        }
+
+        let (ports, protocol) = match spec.rsplit_once('/') {
+            Some((ports, protocol)) => {
+                let protocol = match protocol {
+                    "tcp" => Protocol::Tcp,
+                    "udp" => Protocol::Udp,
+                    "sctp" => Protocol::Sctp,
+                    protocol => {
+                        return Err(error(PortErrorReason::InvalidProtocol(
+                            protocol.to_string(),
+                        )))
+                    }
+                };
+                (ports, Some(protocol))
+            }
+            None => (spec, None),
+        };
+
+        // IPv6 addresses contain colons, so they have to be bracketed.
+        let (host_ip, ports) = match ports.strip_prefix('[') {
+            Some(rest) => {
+                let (ip, ports) = rest
+                    .split_once("]:")
+                    .ok_or_else(|| error(PortErrorReason::InvalidHostIp(rest.to_string())))?;
+                (Some(ip), ports)
+            }
+            None => match ports.matches(':').count() {
+                0 | 1 => (None, ports),
+                2 => ports
+                    .split_once(':')
+                    .map(|(ip, ports)| (Some(ip), ports))
+                    .unwrap_or((None, ports)),
+                _ => return Err(error(PortErrorReason::TooManyParts)),
+            },
+        };
+
+        let host_ip = host_ip
+            .map(|ip| {
+                ip.parse::<IpAddr>()
+                    .map_err(|_| error(PortErrorReason::InvalidHostIp(ip.to_string())))
+            })
+            .transpose()?;
+
+        let (host_port, container_port) = match ports.split_once(':') {
+            Some(("", container_port)) => (None, container_port),
+            Some((host_port, container_port)) => (Some(host_port), container_port),
+            None => (None, ports),
+        };
+
+        let port = |port: &str| {
+            if port.contains('-') {
+                return Err(error(PortErrorReason::Range(port.to_string())));
+            }
+            match port.parse::<u16>() {
+                Ok(0) | Err(_) => Err(error(PortErrorReason::InvalidPort(port.to_string()))),
+                Ok(port) => Ok(port),
+            }
+        };
+
+        Ok(Self {
+            host_ip,
+            host_port: host_port.map(port).transpose()?,
+            container_port: port(container_port)?,
+            protocol,
+        })
+    }
+}
+
+impl Display for PortMapping {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.host_ip {
+            Some(IpAddr::V6(ip)) => write!(f, "[{ip}]:")?,
+            Some(IpAddr::V4(ip)) => write!(f, "{ip}:")?,
+            None => {}
+        }
+        match self.host_port {
+            Some(host_port) => write!(f, "{host_port}:")?,
+            None if self.host_ip.is_some() => write!(f, ":")?,
+            None => {}
+        }
+        write!(f, "{}", self.container_port)?;
+        if let Some(protocol) = self.protocol {
+            write!(f, "/{protocol}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The error returned when a port spec can't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortError {
+    /// The spec that failed to parse.
+    pub spec: String,
+    pub reason: PortErrorReason,
+}
+
+/// Why a port spec is invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortErrorReason {
+    Empty,
+    TooManyParts,
+    InvalidPort(String),
+    Range(String),
+    InvalidProtocol(String),
+    InvalidHostIp(String),
+}
+
+impl Display for PortError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid port `{}`: ", self.spec)?;
+        match &self.reason {
+            PortErrorReason::Empty => write!(f, "the spec is empty"),
+            PortErrorReason::TooManyParts => write!(
+                f,
+                "expected `[ip:][host_port:]container_port[/protocol]`, bracket IPv6 addresses"
+            ),
+            PortErrorReason::InvalidPort(port) => {
+                write!(f, "`{port}` is not a port number between 1 and 65535")
+            }
+            PortErrorReason::Range(range) => {
+                write!(f, "port ranges like `{range}` are not supported")
+            }
+            PortErrorReason::InvalidProtocol(protocol) => write!(
+                f,
+                "unknown protocol `{protocol}`, expected `tcp`, `udp` or `sctp`"
+            ),
+            PortErrorReason::InvalidHostIp(ip) => write!(f, "`{ip}` is not an IP address"),
+        }
+    }
+}
+
+impl std::error::Error for PortError {}
+
+/// Represents a volume mounted in a container. Strings are parsed with the
+/// docker syntax `[source:]destination[:options]`.
+#[derive(Debug, Setters, Clone, Default, PartialEq, Eq)]
 #[setters(strip_option, into)]
 pub struct Volume {
-    /// The source path of the volume.
-    pub source: String,
+    /// The host path or named volume to mount, an anonymous volume when
+    /// unset.
+    pub source: Option<String>,
 
-    /// The destination path of the volume.
+    /// The absolute path of the volume inside the container.
     pub destination: String,
+
+    /// Mounts the volume read-only.
+    pub read_only: bool,
+
+    /// The SELinux label of a bind mount.
+    pub selinux: Option<SeLinux>,
+
+    /// The bind propagation of a bind mount.
+    pub propagation: Option<Propagation>,
+
+    /// Doesn't copy the data of the image into an empty named volume.
+    pub nocopy: bool,
 }
 
 impl Volume {
-    /// Creates a new `Volume` from a string representation.
-    pub fn new(volume_str: &str) -> Option<Self> {
-        let parts: Vec<&str> = volume_str.split(':').collect();
-        if parts.len() == 2 {
-            Some(Self {
-                source: parts[0].to_string(),
-                destination: parts[1].to_string(),
-            })
-        } else {
-            None
+    /// Creates a new `Volume` mounting `source` at `destination`.
+    pub fn new<S: ToString, D: ToString>(source: S, destination: D) -> Self {
+        Self {
+            source: Some(source.to_string()),
+            destination: destination.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a new anonymous `Volume` mounted at `destination`.
+    pub fn anonymous<D: ToString>(destination: D) -> Self {
+        Self { destination: destination.to_string(), ..Default::default() }
+    }
+
+    /// Whether the volume mounts a host path rather than a named volume.
+    pub fn is_bind_mount(&self) -> bool {
+        self.source
+            .as_deref()
+            .is_some_and(|source| source.starts_with(['.', '~']) || is_absolute(source))
+    }
+}
+
+/// The SELinux relabeling of a bind mount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeLinux {
+    /// `z`, the content is shared between containers.
+    Shared,
+
+    /// `Z`, the content is private to the container.
+    Private,
+}
+
+/// The propagation of mounts created inside a bind mount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Propagation {
+    Shared,
+    Slave,
+    Private,
+    RShared,
+    RSlave,
+    RPrivate,
+}
+
+impl Propagation {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Shared => "shared",
+            Self::Slave => "slave",
+            Self::Private => "private",
+            Self::RShared => "rshared",
+            Self::RSlave => "rslave",
+            Self::RPrivate => "rprivate",
+        }
+    }
+}
+
+/// Whether `path` is absolute on unix or windows.
+fn is_absolute(path: &str) -> bool {
+    path.starts_with(['/', '\\']) || is_drive(path)
+}
+
+/// Whether `path` starts with a windows drive, for eg: `C:\`.
+fn is_drive(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && matches!(bytes[2], b'\\' | b'/')
+}
+
+/// Splits a volume spec on `:`, keeping windows drives like `C:\data`
+/// together.
+fn split_volume(spec: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (index, _) in spec.match_indices(':') {
+        if index == start + 1 && is_drive(&spec[start..]) {
+            continue;
+        }
+        parts.push(&spec[start..index]);
+        start = index + 1;
+    }
+    parts.push(&spec[start..]);
+    parts
+}
+
+impl FromStr for Volume {
+    type Err = VolumeError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let error = |reason: VolumeErrorReason| VolumeError { spec: spec.to_string(), reason };
+
+        if spec.trim().is_empty() {
+            return Err(error(VolumeErrorReason::Empty));
+        }
+
+        let (source, destination, options) = match split_volume(spec)[..] {
+            [destination] => (None, destination, None),
+            [source, destination] => (Some(source), destination, None),
+            [source, destination, options] => (Some(source), destination, Some(options)),
+            _ => return Err(error(VolumeErrorReason::TooManyParts)),
+        };
+
+        if source == Some("") {
+            return Err(error(VolumeErrorReason::EmptySource));
+        }
+        if !is_absolute(destination) {
+            return Err(error(VolumeErrorReason::RelativeDestination(
+                destination.to_string(),
+            )));
         }
+
+        let mut volume = Self {
+            source: source.map(str::to_string),
+            destination: destination.to_string(),
+            ..Default::default()
+        };
+
+        let mut mode = None;
+        for option in options.into_iter().flat_map(|options| options.split(',')) {
+            let conflict = |previous: &str| {
+                error(VolumeErrorReason::ConflictingOptions(
+                    previous.to_string(),
+                    option.to_string(),
+                ))
+            };
+            match option {
+                "ro" | "rw" => {
+                    if let Some(previous) = mode.replace(option) {
+                        return Err(conflict(previous));
+                    }
+                    volume.read_only = option == "ro";
+                }
+                "z" | "Z" => {
+                    let selinux = if option == "z" {
+                        SeLinux::Shared
+                    } else {
+                        SeLinux::Private
+                    };
+                    if let Some(previous) = volume.selinux.replace(selinux) {
+                        return Err(conflict(if previous == SeLinux::Shared {
+                            "z"
+                        } else {
+                            "Z"
+                        }));
+                    }
+                }
+                "nocopy" => volume.nocopy = true,
+                option => {
+                    let propagation = [
+                        Propagation::Shared,
+                        Propagation::Slave,
+                        Propagation::Private,
+                        Propagation::RShared,
+                        Propagation::RSlave,
+                        Propagation::RPrivate,
+                    ]
+                    .into_iter()
+                    .find(|propagation| propagation.as_str() == option)
+                    .ok_or_else(|| error(VolumeErrorReason::InvalidOption(option.to_string())))?;
+                    if let Some(previous) = volume.propagation.replace(propagation) {
+                        return Err(conflict(previous.as_str()));
+                    }
+                }
+            }
+        }
+
+        Ok(volume)
+    }
+}
+
+impl Display for Volume {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(source) = &self.source {
+            write!(f, "{source}:")?;
+        }
+        write!(f, "{}", self.destination)?;
+
+        let mut options = Vec::new();
+        if self.read_only {
+            options.push("ro");
+        }
+        match self.selinux {
+            Some(SeLinux::Shared) => options.push("z"),
+            Some(SeLinux::Private) => options.push("Z"),
+            None => {}
+        }
+        if let Some(propagation) = self.propagation {
+            options.push(propagation.as_str());
+        }
+        if self.nocopy {
+            options.push("nocopy");
+        }
+        if !options.is_empty() {
+            write!(f, ":{}", options.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for Volume {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Volume {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// The error returned when a volume spec can't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VolumeError {
+    /// The spec that failed to parse.
+    pub spec: String,
+    pub reason: VolumeErrorReason,
+}
+
+/// Why a volume spec is invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VolumeErrorReason {
+    Empty,
+    EmptySource,
+    TooManyParts,
+    RelativeDestination(String),
+    InvalidOption(String),
+    ConflictingOptions(String, String),
+}
+
+impl Display for VolumeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid volume `{}`: ", self.spec)?;
+        match &self.reason {
+            VolumeErrorReason::Empty => write!(f, "the spec is empty"),
+            VolumeErrorReason::EmptySource => write!(f, "the source is empty"),
+            VolumeErrorReason::TooManyParts => {
+                write!(f, "expected `[source:]destination[:options]`")
+            }
+            VolumeErrorReason::RelativeDestination(destination) => {
+                write!(f, "the destination `{destination}` is not an absolute path")
+            }
+            VolumeErrorReason::InvalidOption(option) => write!(
+                f,
+                "unknown option `{option}`, expected `ro`, `rw`, `z`, `Z`, `nocopy` or a bind propagation"
+            ),
+            VolumeErrorReason::ConflictingOptions(first, second) => {
+                write!(f, "the options `{first}` and `{second}` conflict")
+            }
+        }
+    }
+}
+
+impl std::error::Error for VolumeError {}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_docker_options() {
+        let container = Container::new("postgres:17")
+            .options("--privileged")
+            .health_check(
+                HealthCheck::new("pg_isready -U postgres")
+                    .interval(Duration::from_secs(10))
+                    .timeout(Duration::from_millis(500))
+                    .retries(5u32),
+            )
+            .network("host")
+            .cpus("1.5")
+            .memory("512m")
+            .entrypoint("/bin/sh");
+
+        assert_eq!(
+            container.docker_options().unwrap(),
+            "--privileged --health-cmd \"pg_isready -U postgres\" --health-interval 10s \
+             --health-timeout 500ms --health-retries 5 --network host --cpus 1.5 \
+             --memory 512m --entrypoint /bin/sh"
+        );
+    }
+
+    #[test]
+    fn test_docker_options_empty() {
+        assert_eq!(Container::new("redis").docker_options(), None);
+    }
+
+    #[test]
+    fn test_port_round_trip() {
+        for spec in [
+            "8080:80",
+            "127.0.0.1:5432:5432/tcp",
+            "127.0.0.1::5432",
+            "[::1]:8080:80",
+            "53/udp",
+            "5353:53/udp",
+            "9000/sctp",
+        ] {
+            let port = spec.parse::<Port>().unwrap();
+            assert!(matches!(port, Port::Mapping(_)), "{spec}");
+            assert_eq!(port.to_string(), spec);
+        }
+
+        assert_eq!("80".parse::<Port>().unwrap(), Port::Number(80));
+    }
+
+    #[test]
+    fn test_port_mapping_fields() {
+        let mapping = "127.0.0.1:5432:5432/tcp".parse::<PortMapping>().unwrap();
+        assert_eq!(
+            mapping,
+            PortMapping::new(5432)
+                .host_ip(IpAddr::from([127, 0, 0, 1]))
+                .host_port(5432u16)
+                .protocol(Protocol::Tcp)
+        );
+    }
+
+    #[test]
+    fn test_port_errors() {
+        let error = |spec: &str| spec.parse::<Port>().unwrap_err().to_string();
+
+        assert_eq!(
+            error("8080:http"),
+            "invalid port `8080:http`: `http` is not a port number between 1 and 65535"
+        );
+        assert_eq!(
+            error("53/icmp"),
+            "invalid port `53/icmp`: unknown protocol `icmp`, expected `tcp`, `udp` or `sctp`"
+        );
+        assert_eq!(
+            error("localhost:80:80"),
+            "invalid port `localhost:80:80`: `localhost` is not an IP address"
+        );
+        assert_eq!(
+            error("8000-8010:80"),
+            "invalid port `8000-8010:80`: port ranges like `8000-8010` are not supported"
+        );
+        assert_eq!(error(""), "invalid port ``: the spec is empty");
+    }
+
+    #[test]
+    fn test_volume_round_trip() {
+        for spec in [
+            "/data",
+            "my_docker_volume:/volume_mount",
+            "/source/directory:/destination/directory:ro",
+            "./cache:/cache:ro,z,rslave",
+            "data:/data:nocopy",
+            "C:\\data:C:\\app:ro",
+            "C:\\data:/data",
+        ] {
+            assert_eq!(spec.parse::<Volume>().unwrap().to_string(), spec);
+        }
+
+        // `rw` is the default, so it isn't written back.
+        assert_eq!(
+            "/src:/dst:rw".parse::<Volume>().unwrap().to_string(),
+            "/src:/dst"
+        );
+    }
+
+    #[test]
+    fn test_volume_fields() {
+        let volume = "C:\\data:C:\\app:ro".parse::<Volume>().unwrap();
+        assert_eq!(volume, Volume::new("C:\\data", "C:\\app").read_only(true));
+        assert!(volume.is_bind_mount());
+        assert!(!Volume::new("cargo", "/cargo").is_bind_mount());
+        assert_eq!(Volume::anonymous("/data").to_string(), "/data");
+    }
+
+    #[test]
+    fn test_volume_errors() {
+        let error = |spec: &str| spec.parse::<Volume>().unwrap_err().to_string();
+
+        assert_eq!(
+            error("/src:/dst:ro,rw"),
+            "invalid volume `/src:/dst:ro,rw`: the options `ro` and `rw` conflict"
+        );
+        assert_eq!(
+            error("/src:/dst:cached"),
+            "invalid volume `/src:/dst:cached`: unknown option `cached`, expected `ro`, `rw`, `z`, `Z`, `nocopy` or a bind propagation"
+        );
+        assert_eq!(
+            error("/src:dst"),
+            "invalid volume `/src:dst`: the destination `dst` is not an absolute path"
+        );
+        assert_eq!(
+            error("/a:/b:ro:extra"),
+            "invalid volume `/a:/b:ro:extra`: expected `[source:]destination[:options]`"
+        );
+    }
+
+    #[test]
+    fn test_container_serde_round_trip() {
+        let yaml = r#"image: node:20
+ports:
+- 80
+- 8080:80
+- 127.0.0.1:5353:53/udp
+- ${{ matrix.port }}
+volumes:
+- /data
+- ./src:/app/src:ro
+"#;
+        let container: Container = serde_yml::from_str(yaml).unwrap();
+        assert_eq!(
+            container.ports.as_ref().unwrap()[3],
+            Port::Name("${{ matrix.port }}".to_string())
+        );
+        assert!(container.volumes.as_ref().unwrap()[1].read_only);
+
+        let value = serde_json::to_value(&container).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "image": "node:20",
+                "ports": [80, "8080:80", "127.0.0.1:5353:53/udp", "${{ matrix.port }}"],
+                "volumes": ["/data", "./src:/app/src:ro"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_container_rejects_invalid_port() {
+        let error = serde_yml::from_str::<Container>("image: node\nports:\n- 80:http\n")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("`http` is not a port number"), "{error}");
     }
 }
@@ -0,0 +1,420 @@
+//!
+//! Builds release binaries for many targets from a single matrix job. Each
+//! target is built on the runner OS that can build it, with plain `cargo`
+//! when the runner has the linker it needs, `cargo-zigbuild` for Linux
+//! targets zig can link, and `cross` for everything else.
+
+use derive_setters::Setters;
+use indexmap::IndexMap;
+use serde_json::{json, Value};
+
+use crate::toolchain::{Abi, Arch, RunnerOs, System, Target, Toolchain};
+use crate::{Cargo, Env, Expression, Job, Step, Strategy, Use};
+
+/// The tool that builds a target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CrossTool {
+    /// Plain `cargo build`, with a system linker when cross-compiling.
+    Cargo,
+
+    /// [`cross`](https://github.com/cross-rs/cross), which builds in a
+    /// docker image that has the toolchain of the target.
+    Cross,
+
+    /// [`cargo-zigbuild`](https://github.com/rust-cross/cargo-zigbuild),
+    /// which links with zig.
+    Zigbuild,
+}
+
+impl CrossTool {
+    /// The name of the tool in the matrix, for eg: `zigbuild`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Cargo => "cargo",
+            Self::Cross => "cross",
+            Self::Zigbuild => "zigbuild",
+        }
+    }
+
+    /// The command that builds with the tool.
+    pub fn build_command(&self) -> &'static str {
+        match self {
+            Self::Cargo => "cargo build",
+            Self::Cross => "cross build",
+            Self::Zigbuild => "cargo zigbuild",
+        }
+    }
+}
+
+/// The Debian package and the linker of the GNU toolchain that cross-links
+/// a Linux target from an x86_64 runner.
+fn gnu_linker(target: &Target) -> Option<(&'static str, &'static str)> {
+    let linker = match (target.arch, target.abi?) {
+        (Arch::Aarch64, Abi::Gnu) => ("gcc-aarch64-linux-gnu", "aarch64-linux-gnu-gcc"),
+        (Arch::I686, Abi::Gnu) => ("gcc-i686-linux-gnu", "i686-linux-gnu-gcc"),
+        (Arch::Armv7, Abi::GnuEabihf) => ("gcc-arm-linux-gnueabihf", "arm-linux-gnueabihf-gcc"),
+        (Arch::Riscv64gc, Abi::Gnu) => ("gcc-riscv64-linux-gnu", "riscv64-linux-gnu-gcc"),
+        (Arch::Powerpc64le, Abi::Gnu) => ("gcc-powerpc64le-linux-gnu", "powerpc64le-linux-gnu-gcc"),
+        (Arch::S390x, Abi::Gnu) => ("gcc-s390x-linux-gnu", "s390x-linux-gnu-gcc"),
+        _ => return None,
+    };
+    Some(linker)
+}
+
+/// How a single target is built.
+#[derive(Clone, Debug, PartialEq, Eq, Setters)]
+#[setters(strip_option, into)]
+pub struct CrossTarget {
+    /// The target to build for.
+    pub target: Target,
+
+    /// The tool that builds the target.
+    pub tool: CrossTool,
+
+    /// The label of the runner that builds the target.
+    pub runs_on: String,
+
+    /// The Debian packages installed before building, for eg: the linker.
+    pub packages: Vec<String>,
+
+    /// The linker set as `CARGO_TARGET_<TRIPLE>_LINKER`.
+    pub linker: Option<String>,
+}
+
+impl CrossTarget {
+    /// Chooses how to build `target`: natively on macOS and Windows runners,
+    /// and on an `ubuntu-latest` runner for everything else.
+    pub fn new(target: Target) -> Self {
+        let mut cross = Self {
+            target,
+            tool: CrossTool::Cargo,
+            runs_on: "ubuntu-latest".to_string(),
+            packages: Vec::new(),
+            linker: None,
+        };
+
+        match target.native_runner_os() {
+            Some(RunnerOs::MacOs) => cross.runs_on = "macos-latest".to_string(),
+            Some(RunnerOs::Windows) => cross.runs_on = "windows-latest".to_string(),
+            _ if target.system != System::Linux => {
+                // wasm has no linker to set up, other systems need `cross`.
+                if target.arch != Arch::Wasm32 {
+                    cross.tool = CrossTool::Cross;
+                }
+            }
+            _ if target.abi == Some(Abi::Musl) && target.arch == Arch::X86_64 => {
+                cross.packages.push("musl-tools".to_string());
+            }
+            _ if target.arch == Arch::X86_64 => {}
+            _ => match gnu_linker(&target) {
+                Some((package, linker)) => {
+                    cross.packages.push(package.to_string());
+                    cross.linker = Some(linker.to_string());
+                }
+                None if matches!(
+                    target.abi,
+                    Some(Abi::Musl | Abi::MuslEabi | Abi::MuslEabihf)
+                ) =>
+                {
+                    cross.tool = CrossTool::Zigbuild;
+                }
+                None => cross.tool = CrossTool::Cross,
+            },
+        }
+
+        cross
+    }
+
+    /// The `CARGO_TARGET_<TRIPLE>_LINKER` variable and its value, if the
+    /// target needs a linker.
+    pub fn linker_env(&self) -> Option<(String, String)> {
+        let triple = self
+            .target
+            .to_string()
+            .to_uppercase()
+            .replace(['-', '.'], "_");
+        self.linker
+            .clone()
+            .map(|linker| (format!("CARGO_TARGET_{triple}_LINKER"), linker))
+    }
+
+    /// The entry of the target in the build matrix.
+    fn matrix_entry(&self) -> Value {
+        json!({
+            "target": self.target.to_string(),
+            "os": self.runs_on,
+            "tool": self.tool.as_str(),
+            "build": self.tool.build_command(),
+            "packages": self.packages.join(" "),
+        })
+    }
+}
+
+impl From<Target> for CrossTarget {
+    fn from(value: Target) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Builds release binaries for a list of targets.
+#[derive(Clone, Setters)]
+#[setters(strip_option, into)]
+pub struct CrossBuild {
+    /// The targets to build.
+    pub targets: Vec<CrossTarget>,
+
+    /// The options of the build, for eg: the packages and features. Its
+    /// command is replaced by the build command of each target, and its
+    /// target by the one of the matrix entry.
+    pub cargo: Cargo,
+
+    /// The Rust toolchain, the target of the matrix entry is added to it.
+    pub toolchain: Toolchain,
+}
+
+impl Default for CrossBuild {
+    fn default() -> Self {
+        Self {
+            targets: Vec::new(),
+            cargo: Cargo::new("build").release(true),
+            toolchain: Toolchain::default().add_stable(),
+        }
+    }
+}
+
+impl CrossBuild {
+    /// Creates a new `CrossBuild` for `targets`, each built the way
+    /// [`CrossTarget::new`] chooses.
+    pub fn new(targets: impl IntoIterator<Item = Target>) -> Self {
+        Self::default().targets(
+            targets
+                .into_iter()
+                .map(CrossTarget::new)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Adds a target, for eg: one with a custom tool or runner.
+    pub fn add_target<T: Into<CrossTarget>>(mut self, target: T) -> Self {
+        self.targets.push(target.into());
+        self
+    }
+
+    fn uses(&self, tool: CrossTool) -> bool {
+        self.targets.iter().any(|target| target.tool == tool)
+    }
+
+    /// The `strategy.matrix` with one entry per target. Every entry has the
+    /// `target`, the `os` of the runner, the `tool` and its `build` command,
+    /// and the Debian `packages` to install.
+    pub fn strategy(&self) -> Strategy {
+        let include = self
+            .targets
+            .iter()
+            .map(CrossTarget::matrix_entry)
+            .collect::<Vec<_>>();
+
+        Strategy::default()
+            .fail_fast(false)
+            .matrix(json!({ "include": include }))
+    }
+
+    /// The build command of the matrix entry, with the options of
+    /// [`CrossBuild::cargo`].
+    fn build_command(&self) -> String {
+        let mut cargo = self.cargo.clone();
+        cargo.target = None;
+
+        let mut command = vec!["${{ matrix.build }}".to_string()];
+        command.extend(
+            cargo
+                .args
+                .iter()
+                .map(|arg| arg.trim().to_string())
+                .filter(|arg| !arg.is_empty()),
+        );
+
+        let mut options = cargo.options();
+        let trailing = options
+            .iter()
+            .position(|option| option == "--")
+            .map(|index| options.split_off(index))
+            .unwrap_or_default();
+        command.extend(options);
+        command.push("--target ${{ matrix.target }}".to_string());
+        command.extend(trailing);
+
+        command.join(" ")
+    }
+
+    /// The matrix job that builds every target. Only the setup steps needed
+    /// by the targets are added, and each runs only for the entries that
+    /// need it.
+    pub fn job(&self) -> Job {
+        let linkers = self
+            .targets
+            .iter()
+            .filter_map(CrossTarget::linker_env)
+            .map(|(key, linker)| (key, Value::from(linker)))
+            .collect::<IndexMap<_, _>>();
+
+        let mut job = Job::new("Build ${{ matrix.target }}")
+            .runs_on("${{ matrix.os }}")
+            .strategy(self.strategy())
+            .add_step(Step::checkout())
+            .add_step(
+                Step::<Use>::from(self.toolchain.clone())
+                    .add_with(("target", "${{ matrix.target }}")),
+            );
+
+        if !linkers.is_empty() {
+            job = job.add_env(Env::from(linkers));
+        }
+
+        if self
+            .targets
+            .iter()
+            .any(|target| !target.packages.is_empty())
+        {
+            job = job.add_step(
+                Step::new("Install Linkers")
+                    .run("sudo apt-get update && sudo apt-get install -y ${{ matrix.packages }}")
+                    .if_condition(Expression::new("matrix.packages != ''")),
+            );
+        }
+
+        if self.uses(CrossTool::Cross) {
+            job = job.add_step(
+                Step::new("Install cross")
+                    .uses("taiki-e", "install-action", "v2")
+                    .if_condition(Expression::new("matrix.tool == 'cross'"))
+                    .add_with(("tool", "cross")),
+            );
+        }
+
+        if self.uses(CrossTool::Zigbuild) {
+            job = job
+                .add_step(
+                    Step::new("Setup Zig")
+                        .uses("mlugg", "setup-zig", "v2")
+                        .if_condition(Expression::new("matrix.tool == 'zigbuild'")),
+                )
+                .add_step(
+                    Step::new("Install cargo-zigbuild")
+                        .uses("taiki-e", "install-action", "v2")
+                        .if_condition(Expression::new("matrix.tool == 'zigbuild'"))
+                        .add_with(("tool", "cargo-zigbuild")),
+                );
+        }
+
+        let mut build = Step::new("Build").run(self.build_command());
+        if self.cargo.needs_posix_shell() {
+            build = build.shell("bash");
+        }
+
+        job.add_step(build)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_snapshot;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::Workflow;
+
+    #[test]
+    fn test_tool_selection() {
+        let cases = [
+            (
+                "x86_64-unknown-linux-gnu",
+                CrossTool::Cargo,
+                "ubuntu-latest",
+            ),
+            (
+                "x86_64-unknown-linux-musl",
+                CrossTool::Cargo,
+                "ubuntu-latest",
+            ),
+            (
+                "aarch64-unknown-linux-gnu",
+                CrossTool::Cargo,
+                "ubuntu-latest",
+            ),
+            (
+                "aarch64-unknown-linux-musl",
+                CrossTool::Zigbuild,
+                "ubuntu-latest",
+            ),
+            ("x86_64-unknown-freebsd", CrossTool::Cross, "ubuntu-latest"),
+            ("aarch64-linux-android", CrossTool::Cross, "ubuntu-latest"),
+            ("wasm32-wasip1", CrossTool::Cargo, "ubuntu-latest"),
+            ("aarch64-apple-darwin", CrossTool::Cargo, "macos-latest"),
+            ("x86_64-apple-darwin", CrossTool::Cargo, "macos-latest"),
+            (
+                "aarch64-pc-windows-msvc",
+                CrossTool::Cargo,
+                "windows-latest",
+            ),
+        ];
+
+        for (target, tool, runs_on) in cases {
+            let cross = CrossTarget::new(target.parse().unwrap());
+            assert_eq!(
+                (cross.tool, cross.runs_on.as_str()),
+                (tool, runs_on),
+                "{target}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_linker() {
+        let cross = CrossTarget::new(Target::AARCH64_UNKNOWN_LINUX_GNU);
+        assert_eq!(cross.packages, ["gcc-aarch64-linux-gnu"]);
+        assert_eq!(
+            cross.linker_env(),
+            Some((
+                "CARGO_TARGET_AARCH64_UNKNOWN_LINUX_GNU_LINKER".to_string(),
+                "aarch64-linux-gnu-gcc".to_string()
+            ))
+        );
+
+        let cross = CrossTarget::new(Target::X86_64_UNKNOWN_LINUX_MUSL);
+        assert_eq!(cross.packages, ["musl-tools"]);
+        assert_eq!(cross.linker_env(), None);
+    }
+
+    #[test]
+    fn test_build_command() {
+        let build = CrossBuild::new([Target::X86_64_UNKNOWN_LINUX_GNU]).cargo(
+            Cargo::new("build")
+                .release(true)
+                .add_package("gh-workflow")
+                .add_trailing_arg("-v"),
+        );
+        assert_eq!(
+            build.build_command(),
+            "${{ matrix.build }} --release -p gh-workflow --target ${{ matrix.target }} -- -v"
+        );
+    }
+
+    #[test]
+    fn test_job() {
+        let build = CrossBuild::new([
+            Target::X86_64_UNKNOWN_LINUX_GNU,
+            Target::X86_64_UNKNOWN_LINUX_MUSL,
+            Target::AARCH64_UNKNOWN_LINUX_GNU,
+            Target::AARCH64_UNKNOWN_LINUX_MUSL,
+            Target::AARCH64_APPLE_DARWIN,
+            Target::X86_64_PC_WINDOWS_MSVC,
+        ])
+        .add_target(CrossTarget::new("x86_64-unknown-freebsd".parse().unwrap()));
+
+        assert_snapshot!(Workflow::new("Build")
+            .add_job("build", build.job())
+            .to_string()
+            .unwrap());
+    }
+}
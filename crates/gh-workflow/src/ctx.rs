@@ -5,8 +5,12 @@ use std::marker::PhantomData;
 use std::rc::Rc;
 
 use gh_workflow_macros::Context;
+use indexmap::IndexMap;
 
-use crate::Expression;
+use crate::{Expression, WorkflowInputs};
+
+mod event;
+pub use event::*;
 
 #[derive(Clone)]
 pub struct Context<A> {
@@ -56,6 +60,11 @@ impl<A> Context<A> {
         }
     }
 
+    /// Reinterprets the same path as a context of another type.
+    fn cast<B>(&self) -> Context<B> {
+        Context { marker: PhantomData, step: self.step.clone() }
+    }
+
     pub fn eq(&self, other: Self) -> Context<bool> {
         Context {
             marker: Default::default(),
@@ -87,6 +96,19 @@ impl<A> Context<A> {
     }
 }
 
+impl<A: WorkflowInputs> Context<A> {
+    /// The `inputs` context of a workflow triggered with typed inputs.
+    pub fn inputs() -> Self {
+        Self::new().select("inputs")
+    }
+
+    /// Selects a field of the inputs. Used by `#[derive(WorkflowInputs)]`.
+    #[doc(hidden)]
+    pub fn __select<B>(&self, name: &str) -> Context<B> {
+        self.select(name)
+    }
+}
+
 impl Context<String> {
     pub fn concat(&self, other: Self) -> Self {
         Self {
@@ -101,6 +123,7 @@ impl Context<String> {
 
 #[allow(unused)]
 #[derive(Context)]
+#[context(root)]
 pub struct Github {
     /// The name of the action currently running, or the id of a step.
     action: String,
@@ -124,11 +147,15 @@ pub struct Github {
     api_url: String,
     /// The base_ref or target branch of the pull request in a workflow run.
     base_ref: String,
+    /// The fully-formed ref of the branch or tag that triggered the workflow
+    /// run.
+    #[context(rename = "ref")]
+    ref_: String,
     /// Path on the runner to the file that sets environment variables from
     /// workflow commands.
     env: String,
     /// The full event webhook payload.
-    event: serde_json::Value,
+    event: GithubEvent,
     /// The name of the event that triggered the workflow run.
     event_name: String,
     /// The path to the file on the runner that contains the full event webhook
@@ -190,9 +217,51 @@ pub struct Github {
     workspace: String,
 }
 
-impl Context<Github> {
-    pub fn ref_(&self) -> Context<String> {
-        self.select("ref")
+/// The steps context contains information about the steps in the current job
+/// that have an `id` specified and have already run.
+#[derive(Clone)]
+pub struct Steps;
+
+/// Information about a single step in the steps context.
+#[derive(Clone)]
+pub struct StepInfo;
+
+/// The outputs defined for a step.
+#[derive(Clone)]
+pub struct StepOutputs;
+
+impl Context<Steps> {
+    pub fn steps() -> Self {
+        Self::new().select("steps")
+    }
+
+    /// Selects the step with the given `id`.
+    pub fn get(&self, id: impl Into<String>) -> Context<StepInfo> {
+        self.select(id)
+    }
+}
+
+impl Context<StepInfo> {
+    /// The outputs defined for the step.
+    pub fn outputs(&self) -> Context<StepOutputs> {
+        self.select("outputs")
+    }
+
+    /// The result of the step after `continue-on-error` is applied.
+    pub fn conclusion(&self) -> Context<String> {
+        self.select("conclusion")
+    }
+
+    /// The result of the step before `continue-on-error` is applied.
+    pub fn outcome(&self) -> Context<String> {
+        self.select("outcome")
+    }
+}
+
+impl Context<StepOutputs> {
+    /// Selects the output with the given `name`.
+    pub fn get<A>(&self, name: impl Into<String>) -> Context<A> {
+        self.select(name)
     }
 }
 
@@ -203,8 +272,10 @@ impl fmt::Display for Step {
             Self::Select { name, object } => {
                 if matches!(**object, Self::Root) {
                     write!(f, "{name}")
-                } else {
+                } else if is_property(name) {
                     write!(f, "{object}.{name}")
+                } else {
+                    write!(f, "{object}['{name}']")
                 }
             }
             Self::Eq { left, right } => {
@@ -226,6 +297,15 @@ impl fmt::Display for Step {
     }
 }
 
+/// Checks if `name` can be selected with the `.name` property syntax rather
+/// than the `['name']` index syntax.
+fn is_property(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
 impl<A> fmt::Display for Context<A> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "${{{{ {} }}}}", self.step.to_string().replace('"', ""))
@@ -247,8 +327,21 @@ impl<T: Into<String>> From<T> for Context<String> {
     }
 }
 
+/// An object whose entries are selected by key, for eg: the services of a
+/// job.
+#[derive(Clone)]
+pub struct Map<V>(PhantomData<V>);
+
+impl<V> Context<Map<V>> {
+    /// Selects the entry with the given `key`.
+    pub fn get(&self, key: impl Into<String>) -> Context<V> {
+        self.select(key)
+    }
+}
+
 #[allow(unused)]
 #[derive(Context)]
+#[context(root)]
 /// The job context contains information about the currently running job.
 pub struct Job {
     /// A unique number for each container in a job. This property is only
@@ -257,7 +350,7 @@ pub struct Job {
 
     /// The services configured for a job. This property is only available if
     /// the job uses service containers.
-    services: Services,
+    services: IndexMap<String, Service>,
 
     /// The status of the current job.
     status: JobStatus,
@@ -286,10 +379,16 @@ pub struct Container {
 }
 
 #[derive(Context)]
-
-/// Services configured for a job. This is only available if the job uses
-/// service containers.
-pub struct Services {}
+#[allow(unused)]
+/// A service container configured for a job.
+pub struct Service {
+    /// The ID of the service container
+    id: String,
+    /// The service container network
+    network: String,
+    /// The exposed ports of the service container, keyed by container port.
+    ports: IndexMap<String, String>,
+}
 
 #[cfg(test)]
 mod test {
@@ -310,6 +409,40 @@ mod test {
         assert_eq!(action_path.to_string(), "${{ github.action_path }}");
     }
 
+    #[test]
+    fn test_expr_steps() {
+        let cache_hit: Context<bool> = Context::steps().get("cache").outputs().get("cache-hit");
+
+        assert_eq!(
+            cache_hit.to_string(),
+            "${{ steps.cache.outputs.cache-hit }}"
+        );
+    }
+
+    #[test]
+    fn test_expr_rename_and_roots() {
+        assert_eq!(Context::github().ref_().to_string(), "${{ github.ref }}");
+        assert_eq!(
+            Context::<Job>::job().status().to_string(),
+            "${{ job.status }}"
+        );
+        assert_eq!(
+            Context::<Job>::job().container().id().to_string(),
+            "${{ job.container.id }}"
+        );
+    }
+
+    #[test]
+    fn test_expr_map() {
+        let postgres = Context::<Job>::job().services().get("postgres");
+
+        assert_eq!(postgres.id().to_string(), "${{ job.services.postgres.id }}");
+        assert_eq!(
+            postgres.ports().get("5432").to_string(),
+            "${{ job.services.postgres.ports['5432'] }}"
+        );
+    }
+
     #[test]
     fn test_expr_eq() {
         let github = Context::github();
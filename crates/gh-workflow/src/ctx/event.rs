@@ -0,0 +1,474 @@
+//! Typed contexts for the `github.event` webhook payloads: <https://docs.github.com/en/webhooks/webhook-events-and-payloads>
+
+use indexmap::IndexMap;
+
+use super::{Context, Map};
+use crate::WorkflowInputs;
+
+/// The full event webhook payload. Its shape depends on the event that
+/// triggered the workflow, so it is branched to the payload of that event.
+#[derive(Clone)]
+pub struct GithubEvent;
+
+impl Context<GithubEvent> {
+    /// The payload of a `push` event.
+    pub fn push(&self) -> Context<PushPayload> {
+        self.cast()
+    }
+
+    /// The payload of a `pull_request` or `pull_request_target` event.
+    pub fn pull_request(&self) -> Context<PullRequestPayload> {
+        self.cast()
+    }
+
+    /// The payload of a `release` event.
+    pub fn release(&self) -> Context<ReleasePayload> {
+        self.cast()
+    }
+
+    /// The payload of an `issue_comment` event.
+    pub fn issue_comment(&self) -> Context<IssueCommentPayload> {
+        self.cast()
+    }
+
+    /// The payload of a `workflow_dispatch` event.
+    pub fn workflow_dispatch(&self) -> Context<WorkflowDispatchPayload> {
+        self.cast()
+    }
+
+    /// The payload of a `workflow_run` event.
+    pub fn workflow_run(&self) -> Context<WorkflowRunPayload> {
+        self.cast()
+    }
+
+    /// The payload of a `merge_group` event.
+    pub fn merge_group(&self) -> Context<MergeGroupPayload> {
+        self.cast()
+    }
+}
+
+/// The payloads of the events enabled in a workflow's `on`. Every accessor
+/// returns `None` when its event can't trigger the workflow.
+pub struct EventPayloads<'a> {
+    on: &'a crate::Event,
+}
+
+impl crate::Event {
+    /// The typed `github.event` payloads of the enabled events.
+    pub fn payloads(&self) -> EventPayloads<'_> {
+        EventPayloads { on: self }
+    }
+}
+
+impl EventPayloads<'_> {
+    fn event(&self, enabled: bool) -> Option<Context<GithubEvent>> {
+        enabled.then(|| Context::github().event())
+    }
+
+    pub fn push(&self) -> Option<Context<PushPayload>> {
+        self.event(self.on.push.is_some()).map(|e| e.push())
+    }
+
+    pub fn pull_request(&self) -> Option<Context<PullRequestPayload>> {
+        let enabled = self.on.pull_request.is_some() || self.on.pull_request_target.is_some();
+        self.event(enabled).map(|e| e.pull_request())
+    }
+
+    pub fn release(&self) -> Option<Context<ReleasePayload>> {
+        self.event(self.on.release.is_some()).map(|e| e.release())
+    }
+
+    pub fn issue_comment(&self) -> Option<Context<IssueCommentPayload>> {
+        self.event(self.on.issue_comment.is_some())
+            .map(|e| e.issue_comment())
+    }
+
+    pub fn workflow_dispatch(&self) -> Option<Context<WorkflowDispatchPayload>> {
+        self.event(self.on.workflow_dispatch.is_some())
+            .map(|e| e.workflow_dispatch())
+    }
+
+    pub fn workflow_run(&self) -> Option<Context<WorkflowRunPayload>> {
+        self.event(self.on.workflow_run.is_some())
+            .map(|e| e.workflow_run())
+    }
+
+    pub fn merge_group(&self) -> Option<Context<MergeGroupPayload>> {
+        self.event(self.on.merge_group.is_some())
+            .map(|e| e.merge_group())
+    }
+}
+
+#[allow(unused)]
+#[derive(Context)]
+/// The payload of a `push` event.
+pub struct PushPayload {
+    /// The SHA of the most recent commit on `ref` after the push.
+    after: String,
+    /// The SHA of the most recent commit on `ref` before the push.
+    before: String,
+    /// The base ref, if the push was a tag or a branch.
+    base_ref: String,
+    /// The URL comparing the `before` and `after` commits.
+    compare: String,
+    /// Whether the push created the `ref`.
+    created: bool,
+    /// Whether the push deleted the `ref`.
+    deleted: bool,
+    /// Whether the push was a force push of the `ref`.
+    forced: bool,
+    /// The most recent commit pushed.
+    head_commit: Commit,
+    /// The user who pushed the commits.
+    pusher: GitActor,
+    /// The full git ref that was pushed, for eg: `refs/heads/main`.
+    #[context(rename = "ref")]
+    ref_: String,
+    /// The repository the commits were pushed to.
+    repository: Repository,
+    /// The user that triggered the event.
+    sender: User,
+}
+
+#[allow(unused)]
+#[derive(Context)]
+/// The payload of a `pull_request` or `pull_request_target` event.
+pub struct PullRequestPayload {
+    /// The action that was performed, for eg: `opened` or `synchronize`.
+    action: String,
+    /// The pull request number.
+    number: u64,
+    /// The pull request itself.
+    pull_request: PullRequest,
+    /// The repository of the pull request.
+    repository: Repository,
+    /// The user that triggered the event.
+    sender: User,
+}
+
+#[allow(unused)]
+#[derive(Context)]
+/// The payload of a `release` event.
+pub struct ReleasePayload {
+    /// The action that was performed, for eg: `published`.
+    action: String,
+    /// The release itself.
+    release: Release,
+    /// The repository of the release.
+    repository: Repository,
+    /// The user that triggered the event.
+    sender: User,
+}
+
+#[allow(unused)]
+#[derive(Context)]
+/// The payload of an `issue_comment` event.
+pub struct IssueCommentPayload {
+    /// The action that was performed, for eg: `created`.
+    action: String,
+    /// The comment itself.
+    comment: Comment,
+    /// The issue or pull request the comment belongs to.
+    issue: Issue,
+    /// The repository of the issue.
+    repository: Repository,
+    /// The user that triggered the event.
+    sender: User,
+}
+
+#[allow(unused)]
+#[derive(Context)]
+/// The payload of a `workflow_dispatch` event.
+pub struct WorkflowDispatchPayload {
+    /// The inputs the workflow was dispatched with.
+    inputs: IndexMap<String, String>,
+    /// The branch or tag the workflow was dispatched on.
+    #[context(rename = "ref")]
+    ref_: String,
+    /// The path of the workflow file.
+    workflow: String,
+    /// The repository of the workflow.
+    repository: Repository,
+    /// The user that triggered the event.
+    sender: User,
+}
+
+impl Context<WorkflowDispatchPayload> {
+    /// The inputs the workflow was dispatched with, typed by a
+    /// `#[derive(WorkflowInputs)]` struct.
+    pub fn typed_inputs<T: WorkflowInputs>(&self) -> Context<T> {
+        self.select("inputs")
+    }
+}
+
+#[allow(unused)]
+#[derive(Context)]
+/// The payload of a `workflow_run` event.
+pub struct WorkflowRunPayload {
+    /// The action that was performed, for eg: `completed`.
+    action: String,
+    /// The workflow run that triggered the event.
+    workflow_run: WorkflowRunInfo,
+    /// The repository of the workflow run.
+    repository: Repository,
+    /// The user that triggered the event.
+    sender: User,
+}
+
+#[allow(unused)]
+#[derive(Context)]
+/// The payload of a `merge_group` event.
+pub struct MergeGroupPayload {
+    /// The action that was performed, for eg: `checks_requested`.
+    action: String,
+    /// The merge group itself.
+    merge_group: MergeGroup,
+    /// The repository of the merge group.
+    repository: Repository,
+    /// The user that triggered the event.
+    sender: User,
+}
+
+#[allow(unused)]
+#[derive(Context)]
+/// A git commit.
+pub struct Commit {
+    /// The SHA of the commit.
+    id: String,
+    /// The commit message.
+    message: String,
+    /// The ISO 8601 timestamp of the commit.
+    timestamp: String,
+    /// The SHA of the tree of the commit.
+    tree_id: String,
+    /// The URL of the commit.
+    url: String,
+    /// The author of the commit.
+    author: GitActor,
+    /// The committer of the commit.
+    committer: GitActor,
+}
+
+#[allow(unused)]
+#[derive(Context)]
+/// The author, committer or pusher of git commits.
+pub struct GitActor {
+    /// The git name.
+    name: String,
+    /// The git email.
+    email: String,
+    /// The GitHub username, if known.
+    username: String,
+}
+
+#[allow(unused)]
+#[derive(Context)]
+/// A GitHub user or app.
+pub struct User {
+    /// The login of the user.
+    login: String,
+    /// The ID of the user.
+    id: u64,
+    /// The type of account, for eg: `User` or `Bot`.
+    #[context(rename = "type")]
+    type_: String,
+}
+
+#[allow(unused)]
+#[derive(Context)]
+/// A GitHub repository.
+pub struct Repository {
+    /// The ID of the repository.
+    id: u64,
+    /// The name of the repository.
+    name: String,
+    /// The owner and name of the repository.
+    full_name: String,
+    /// The default branch of the repository.
+    default_branch: String,
+    /// The URL of the repository.
+    html_url: String,
+    /// Whether the repository is private.
+    private: bool,
+    /// Whether the repository is a fork.
+    fork: bool,
+    /// The owner of the repository.
+    owner: User,
+}
+
+#[allow(unused)]
+#[derive(Context)]
+/// A pull request.
+pub struct PullRequest {
+    /// The pull request number.
+    number: u64,
+    /// The title of the pull request.
+    title: String,
+    /// The body of the pull request.
+    body: String,
+    /// The state of the pull request, `open` or `closed`.
+    state: String,
+    /// Whether the pull request is a draft.
+    draft: bool,
+    /// Whether the pull request was merged.
+    merged: bool,
+    /// The URL of the pull request.
+    html_url: String,
+    /// The branch the changes are pulled from.
+    head: PullRequestRef,
+    /// The branch the changes are pulled into.
+    base: PullRequestRef,
+    /// The author of the pull request.
+    user: User,
+}
+
+#[allow(unused)]
+#[derive(Context)]
+/// The head or base branch of a pull request.
+pub struct PullRequestRef {
+    /// The branch name.
+    #[context(rename = "ref")]
+    ref_: String,
+    /// The SHA of the commit at the tip of the branch.
+    sha: String,
+    /// The branch name prefixed with its owner, for eg: `octocat:main`.
+    label: String,
+    /// The repository of the branch.
+    repo: Repository,
+}
+
+#[allow(unused)]
+#[derive(Context)]
+/// A release.
+pub struct Release {
+    /// The ID of the release.
+    id: u64,
+    /// The name of the tag of the release.
+    tag_name: String,
+    /// The branch or commit the tag is created from.
+    target_commitish: String,
+    /// The name of the release.
+    name: String,
+    /// The body of the release.
+    body: String,
+    /// Whether the release is a draft.
+    draft: bool,
+    /// Whether the release is a prerelease.
+    prerelease: bool,
+    /// The URL of the release.
+    html_url: String,
+}
+
+#[allow(unused)]
+#[derive(Context)]
+/// An issue, or a pull request viewed as an issue.
+pub struct Issue {
+    /// The issue number.
+    number: u64,
+    /// The title of the issue.
+    title: String,
+    /// The body of the issue.
+    body: String,
+    /// The state of the issue, `open` or `closed`.
+    state: String,
+    /// The URL of the issue.
+    html_url: String,
+    /// The author of the issue.
+    user: User,
+    /// Links to the pull request, only present if the issue is a pull request.
+    pull_request: Map<String>,
+}
+
+#[allow(unused)]
+#[derive(Context)]
+/// A comment on an issue or pull request.
+pub struct Comment {
+    /// The ID of the comment.
+    id: u64,
+    /// The body of the comment.
+    body: String,
+    /// The URL of the comment.
+    html_url: String,
+    /// The author of the comment.
+    user: User,
+}
+
+#[allow(unused)]
+#[derive(Context)]
+/// A run of a workflow.
+pub struct WorkflowRunInfo {
+    /// The ID of the run.
+    id: u64,
+    /// The name of the workflow.
+    name: String,
+    /// The event that triggered the run.
+    event: String,
+    /// The status of the run, for eg: `completed`.
+    status: String,
+    /// The conclusion of the run, for eg: `success` or `failure`.
+    conclusion: String,
+    /// The branch the run ran on.
+    head_branch: String,
+    /// The SHA of the commit the run ran on.
+    head_sha: String,
+    /// The run number.
+    run_number: u64,
+    /// The attempt number of the run.
+    run_attempt: u64,
+    /// The URL of the run.
+    html_url: String,
+}
+
+#[allow(unused)]
+#[derive(Context)]
+/// A merge group of the merge queue.
+pub struct MergeGroup {
+    /// The SHA of the merge group.
+    head_sha: String,
+    /// The full ref of the merge group.
+    head_ref: String,
+    /// The SHA of the merge group's parent commit.
+    base_sha: String,
+    /// The full ref of the branch the merge group will be merged into.
+    base_ref: String,
+    /// The commit of the merge group.
+    head_commit: Commit,
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{Event, Push};
+
+    #[test]
+    fn test_event_payloads() {
+        let event = Context::github().event();
+
+        assert_eq!(
+            event.pull_request().pull_request().head().sha().to_string(),
+            "${{ github.event.pull_request.head.sha }}"
+        );
+        assert_eq!(
+            event.push().head_commit().message().to_string(),
+            "${{ github.event.head_commit.message }}"
+        );
+        assert_eq!(
+            event.workflow_dispatch().inputs().get("tag").to_string(),
+            "${{ github.event.inputs.tag }}"
+        );
+    }
+
+    #[test]
+    fn test_enabled_payloads() {
+        let on = Event::default().push(Push::default());
+        let payloads = on.payloads();
+
+        assert_eq!(
+            payloads.push().map(|push| push.after().to_string()),
+            Some("${{ github.event.after }}".to_string())
+        );
+        assert!(payloads.pull_request().is_none());
+    }
+}
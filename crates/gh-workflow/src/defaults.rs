@@ -38,20 +38,61 @@ pub struct RunDefaults {
     pub working_directory: Option<String>,
 }
 
-/// Represents default settings for retries.
-#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+/// Represents default settings for retries. These apply to every step that
+/// doesn't set its own [`RetryStrategy`].
+#[derive(Debug, Setters, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
+#[setters(strip_option, into)]
 pub struct RetryDefaults {
-    /// The maximum number of retry attempts.
+    /// The maximum number of attempts, including the first one.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_attempts: Option<u32>,
+
+    /// The number of seconds to wait before retrying a `run` step.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delay_seconds: Option<u32>,
+
+    /// Whether the delay doubles after every failed attempt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exponential_backoff: Option<bool>,
 }
 
-/// Represents a strategy for retrying jobs.
-#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+/// Represents a strategy for retrying steps. It isn't a GitHub Actions key;
+/// `run` steps executed by bash or sh are wrapped in a bounded retry loop and
+/// other steps are repeated, each copy running only if the previous attempt
+/// failed. A repeated step's id refers to its first attempt only, so it
+/// can't be repeated when the job references it.
+#[derive(Debug, Setters, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
+#[setters(strip_option, into)]
 pub struct RetryStrategy {
-    /// The maximum number of retry attempts.
+    /// The maximum number of attempts, including the first one.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_attempts: Option<u32>,
+
+    /// The number of seconds to wait before retrying a `run` step.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delay_seconds: Option<u32>,
+
+    /// Whether the delay doubles after every failed attempt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exponential_backoff: Option<bool>,
+}
+
+impl RetryStrategy {
+    /// Creates a new `RetryStrategy` with the given maximum number of
+    /// attempts.
+    pub fn new(max_attempts: u32) -> Self {
+        Self { max_attempts: Some(max_attempts), ..Default::default() }
+    }
+}
+
+impl From<RetryDefaults> for RetryStrategy {
+    fn from(defaults: RetryDefaults) -> Self {
+        Self {
+            max_attempts: defaults.max_attempts,
+            delay_seconds: defaults.delay_seconds,
+            exponential_backoff: defaults.exponential_backoff,
+        }
+    }
 }
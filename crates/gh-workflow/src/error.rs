@@ -1,15 +1,257 @@
 //! This module defines the `Error` enum and the `Result` type alias used
 //! throughout the crate.
 
-#[derive(Debug, derive_more::From)]
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+/// The errors that can occur while building, parsing or generating a
+/// workflow.
+#[derive(Debug)]
 pub enum Error {
-    IO(std::io::Error),
-    Yaml(serde_yml::Error),
-    GitHubWorkflowMismatch,
-    JobIdAlreadyExists(String),
-    UTF8(std::string::FromUtf8Error),
-    OutdatedWorkflow,
-    MissingWorkflowFile(std::path::PathBuf),
+    /// An I/O operation failed, optionally on a specific file.
+    IO {
+        path: Option<PathBuf>,
+        source: std::io::Error,
+    },
+
+    /// A workflow could not be serialized to, or parsed from, YAML.
+    Yaml {
+        /// The name of the workflow, when known.
+        workflow: Option<String>,
+        /// The position in the YAML document where the error occurred.
+        location: Option<serde_yml::Location>,
+        source: Box<serde_yml::Error>,
+    },
+
+    /// The workflow file on disk differs from the generated content.
+    OutdatedWorkflow {
+        path: PathBuf,
+        /// A line diff from the file on disk to the generated content.
+        diff: String,
+    },
+
+    /// The workflow file does not exist on disk.
+    MissingWorkflowFile(PathBuf),
+
+    /// The workflow was rejected by validation.
+    Validation {
+        /// The name of the workflow, when known.
+        workflow: Option<String>,
+        diagnostics: Vec<Diagnostic>,
+    },
+
+    /// The root of the git repository could not be determined.
+    GitDiscovery {
+        reason: String,
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+
+    /// A manifest read at generation time, for eg: `Cargo.toml` or
+    /// `rust-toolchain.toml`, is invalid.
+    Manifest {
+        path: PathBuf,
+        reason: String,
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+}
+
+/// A single problem found while validating a workflow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The location of the offending value, for eg: `jobs.build.steps[0]`.
+    pub path: String,
+
+    /// A human-readable description of the problem.
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn new<P: ToString, M: ToString>(path: P, message: M) -> Self {
+        Self { path: path.to_string(), message: message.to_string() }
+    }
+}
+
+/// Adds a diagnostic for every key in `extra`, which holds the keys that
+/// weren't recognised under `path`.
+pub(crate) fn unknown_keys(
+    path: &str,
+    extra: &indexmap::IndexMap<String, serde_json::Value>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for key in extra.keys() {
+        let path = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{path}.{key}")
+        };
+        diagnostics.push(Diagnostic::new(path, "unknown key"));
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+impl Error {
+    /// Creates an [`Error::IO`] for an operation on `path`.
+    pub(crate) fn io(source: std::io::Error, path: impl Into<PathBuf>) -> Self {
+        Self::IO { path: Some(path.into()), source }
+    }
+
+    /// Creates an [`Error::Manifest`] for the manifest at `path`.
+    pub(crate) fn manifest(path: impl Into<PathBuf>, reason: impl ToString) -> Self {
+        Self::Manifest { path: path.into(), reason: reason.to_string(), source: None }
+    }
+
+    /// Creates an [`Error::Yaml`] for the workflow with the given name.
+    pub(crate) fn yaml(source: serde_yml::Error, workflow: Option<String>) -> Self {
+        Self::Yaml {
+            workflow,
+            location: source.location(),
+            source: Box::new(source),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IO { path: Some(path), source } => {
+                write!(f, "I/O error on {}: {source}", path.display())
+            }
+            Self::IO { path: None, source } => write!(f, "I/O error: {source}"),
+            Self::Yaml { workflow, location, source } => {
+                write!(f, "YAML error")?;
+                if let Some(workflow) = workflow {
+                    write!(f, " in workflow `{workflow}`")?;
+                }
+                if let Some(location) = location {
+                    write!(
+                        f,
+                        " at line {}, column {}",
+                        location.line(),
+                        location.column()
+                    )?;
+                }
+                write!(f, ": {source}")
+            }
+            Self::OutdatedWorkflow { path, diff } => {
+                write!(
+                    f,
+                    "Workflow file is outdated: {}. Regenerate it locally and commit the changes.",
+                    path.display()
+                )?;
+                if !diff.is_empty() {
+                    write!(f, "\n{diff}")?;
+                }
+                Ok(())
+            }
+            Self::MissingWorkflowFile(path) => {
+                write!(f, "Workflow file is missing: {}", path.display())
+            }
+            Self::Validation { workflow, diagnostics } => {
+                write!(f, "Validation failed")?;
+                if let Some(workflow) = workflow {
+                    write!(f, " for workflow `{workflow}`")?;
+                }
+                write!(f, " with {} error(s):", diagnostics.len())?;
+                for diagnostic in diagnostics {
+                    write!(f, "\n  - {diagnostic}")?;
+                }
+                Ok(())
+            }
+            Self::GitDiscovery { reason, .. } => {
+                write!(f, "Unable to locate the git repository root: {reason}")
+            }
+            Self::Manifest { path, reason, .. } => {
+                write!(f, "Invalid manifest {}: {reason}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::IO { source, .. } => Some(source),
+            Self::Yaml { source, .. } => Some(source.as_ref()),
+            Self::GitDiscovery { source: Some(source), .. } => Some(source.as_ref()),
+            Self::Manifest { source: Some(source), .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(source: std::io::Error) -> Self {
+        Self::IO { path: None, source }
+    }
+}
+
+impl From<serde_yml::Error> for Error {
+    fn from(source: serde_yml::Error) -> Self {
+        Self::yaml(source, None)
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_error_is_send_sync() {
+        fn assert_send_sync<T: Send + Sync + 'static>() {}
+        assert_send_sync::<Error>();
+    }
+
+    #[test]
+    fn test_io_error_display_and_source() {
+        let error = Error::io(
+            std::io::Error::new(std::io::ErrorKind::NotFound, "not found"),
+            ".github/workflows/ci.yml",
+        );
+
+        assert_eq!(
+            error.to_string(),
+            "I/O error on .github/workflows/ci.yml: not found"
+        );
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn test_yaml_error_location() {
+        let source = serde_yml::from_str::<serde_yml::Value>("a: [unclosed").unwrap_err();
+        let error = Error::yaml(source, Some("ci".to_string()));
+
+        assert!(error.to_string().starts_with("YAML error in workflow `ci`"));
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn test_validation_error_display() {
+        let error = Error::Validation {
+            workflow: Some("ci".to_string()),
+            diagnostics: vec![
+                Diagnostic::new("jobs.build", "missing `runs-on`"),
+                Diagnostic::new("", "no jobs defined"),
+            ],
+        };
+
+        assert_eq!(
+            error.to_string(),
+            "Validation failed for workflow `ci` with 2 error(s):\n  - jobs.build: missing `runs-on`\n  - no jobs defined"
+        );
+    }
+}
@@ -4,7 +4,9 @@ use derive_setters::Setters;
 use indexmap::IndexMap;
 use merge::Merge;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
+use crate::error::{unknown_keys, Diagnostic};
 use crate::is_default;
 
 /// Represents all possible webhook events that can trigger a workflow
@@ -108,6 +110,11 @@ pub struct Event {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[merge(strategy = merge::option::overwrite_none)]
     pub workflow_run: Option<WorkflowRun>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    #[merge(strategy = crate::merge_extra)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl Event {
@@ -124,6 +131,117 @@ impl Event {
     pub fn add_cron_schedule(self, cron: impl ToString) -> Self {
         self.add_schedule(Schedule::new(cron))
     }
+
+    /// Collects a diagnostic for every key under `on` that isn't modelled.
+    pub(crate) fn unknown_keys(&self, diagnostics: &mut Vec<Diagnostic>) {
+        unknown_keys("on", &self.extra, diagnostics);
+
+        let events = [
+            (
+                "branch_protection_rule",
+                self.branch_protection_rule.as_ref().map(|e| &e.extra),
+            ),
+            ("check_run", self.check_run.as_ref().map(|e| &e.extra)),
+            ("check_suite", self.check_suite.as_ref().map(|e| &e.extra)),
+            ("create", self.create.as_ref().map(|e| &e.extra)),
+            ("delete", self.delete.as_ref().map(|e| &e.extra)),
+            ("deployment", self.deployment.as_ref().map(|e| &e.extra)),
+            (
+                "deployment_status",
+                self.deployment_status.as_ref().map(|e| &e.extra),
+            ),
+            ("discussion", self.discussion.as_ref().map(|e| &e.extra)),
+            (
+                "discussion_comment",
+                self.discussion_comment.as_ref().map(|e| &e.extra),
+            ),
+            (
+                "issue_comment",
+                self.issue_comment.as_ref().map(|e| &e.extra),
+            ),
+            ("issues", self.issues.as_ref().map(|e| &e.extra)),
+            ("label", self.label.as_ref().map(|e| &e.extra)),
+            ("merge_group", self.merge_group.as_ref().map(|e| &e.extra)),
+            ("milestone", self.milestone.as_ref().map(|e| &e.extra)),
+            ("pull_request", self.pull_request.as_ref().map(|e| &e.extra)),
+            (
+                "pull_request_review",
+                self.pull_request_review.as_ref().map(|e| &e.extra),
+            ),
+            (
+                "pull_request_review_comment",
+                self.pull_request_review_comment.as_ref().map(|e| &e.extra),
+            ),
+            (
+                "pull_request_target",
+                self.pull_request_target.as_ref().map(|e| &e.extra),
+            ),
+            ("push", self.push.as_ref().map(|e| &e.extra)),
+            (
+                "registry_package",
+                self.registry_package.as_ref().map(|e| &e.extra),
+            ),
+            ("release", self.release.as_ref().map(|e| &e.extra)),
+            (
+                "repository_dispatch",
+                self.repository_dispatch.as_ref().map(|e| &e.extra),
+            ),
+            ("watch", self.watch.as_ref().map(|e| &e.extra)),
+            (
+                "workflow_call",
+                self.workflow_call.as_ref().map(|e| &e.extra),
+            ),
+            (
+                "workflow_dispatch",
+                self.workflow_dispatch.as_ref().map(|e| &e.extra),
+            ),
+            ("workflow_run", self.workflow_run.as_ref().map(|e| &e.extra)),
+        ];
+
+        for (name, extra) in events {
+            if let Some(extra) = extra {
+                unknown_keys(&format!("on.{name}"), extra, diagnostics);
+            }
+        }
+
+        for (i, schedule) in self.schedule.iter().flatten().enumerate() {
+            unknown_keys(&format!("on.schedule[{i}]"), &schedule.extra, diagnostics);
+        }
+
+        if let Some(call) = &self.workflow_call {
+            for (name, input) in &call.inputs {
+                unknown_keys(
+                    &format!("on.workflow_call.inputs.{name}"),
+                    &input.extra,
+                    diagnostics,
+                );
+            }
+            for (name, output) in &call.outputs {
+                unknown_keys(
+                    &format!("on.workflow_call.outputs.{name}"),
+                    &output.extra,
+                    diagnostics,
+                );
+            }
+            for (name, secret) in &call.secrets {
+                unknown_keys(
+                    &format!("on.workflow_call.secrets.{name}"),
+                    &secret.extra,
+                    diagnostics,
+                );
+            }
+        }
+
+        if let Some(dispatch) = &self.workflow_dispatch {
+            for (name, input) in &dispatch.inputs {
+                unknown_keys(
+                    &format!("on.workflow_dispatch.inputs.{name}"),
+                    &input.extra,
+                    diagnostics,
+                );
+            }
+        }
+    }
 }
 
 /// Types of branch protection rule events
@@ -145,6 +263,10 @@ pub enum BranchProtectionRuleType {
 pub struct BranchProtectionRule {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub types: Vec<BranchProtectionRuleType>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl BranchProtectionRule {
@@ -175,6 +297,10 @@ pub enum CheckRunType {
 pub struct CheckRun {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub types: Vec<CheckRunType>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl CheckRun {
@@ -201,6 +327,10 @@ pub enum CheckSuiteType {
 pub struct CheckSuite {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub types: Vec<CheckSuiteType>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl CheckSuite {
@@ -222,6 +352,10 @@ pub struct Create {
     /// Filter on specific tag names
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl Create {
@@ -249,6 +383,10 @@ pub struct Delete {
     /// Filter on specific tag names
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl Delete {
@@ -274,6 +412,10 @@ pub struct Deployment {
     /// Filter on specific branch names
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub branches: Vec<String>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl Deployment {
@@ -293,6 +435,10 @@ pub struct DeploymentStatus {
     /// Filter on specific deployment states
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub states: Vec<String>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl DeploymentStatus {
@@ -343,6 +489,10 @@ pub struct Discussion {
     /// Filter on specific discussion event types
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub types: Vec<DiscussionType>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl Discussion {
@@ -373,6 +523,10 @@ pub struct DiscussionComment {
     /// Filter on specific discussion comment event types
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub types: Vec<DiscussionCommentType>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl DiscussionComment {
@@ -390,6 +544,10 @@ pub struct IssueComment {
     /// Filter on specific issue comment event types
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub types: Vec<IssueCommentType>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 /// Types of issue comment events
@@ -459,6 +617,10 @@ pub struct Issues {
     /// Filter on specific issue event types
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub types: Vec<IssuesType>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl Issues {
@@ -490,6 +652,10 @@ pub struct Label {
     /// Filter on specific label event types
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub types: Vec<LabelType>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl Label {
@@ -511,6 +677,10 @@ pub enum MergeGroupType {
 pub struct MergeGroup {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub types: Vec<MergeGroupType>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl MergeGroup {
@@ -535,6 +705,10 @@ pub enum MilestoneType {
 pub struct Milestone {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub types: Vec<MilestoneType>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl Milestone {
@@ -576,6 +750,10 @@ pub struct PullRequest {
     /// Ignore specific file paths
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub paths_ignore: Vec<String>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl PullRequest {
@@ -623,6 +801,10 @@ pub struct PullRequestReview {
     /// Filter on specific pull request review event types
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub types: Vec<PullRequestReviewType>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl PullRequestReview {
@@ -653,6 +835,10 @@ pub struct PullRequestReviewComment {
     /// Filter on specific pull request review comment event types
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub types: Vec<PullRequestReviewCommentType>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl PullRequestReviewComment {
@@ -680,6 +866,10 @@ pub struct PullRequestTarget {
     /// Ignore specific file paths
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub paths_ignore: Vec<String>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl PullRequestTarget {
@@ -726,6 +916,10 @@ pub struct Push {
     /// Filter on specific tags
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl Push {
@@ -773,6 +967,10 @@ pub struct RegistryPackage {
     /// Filter on specific registry package event types
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub types: Vec<RegistryPackageType>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl RegistryPackage {
@@ -812,6 +1010,10 @@ pub struct Release {
     /// Filter on specific release event types
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub types: Vec<ReleaseType>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl Release {
@@ -827,6 +1029,10 @@ impl Release {
 pub struct RepositoryDispatch {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub types: Vec<String>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl RepositoryDispatch {
@@ -840,11 +1046,15 @@ impl RepositoryDispatch {
 #[setters(strip_option, into)]
 pub struct Schedule {
     pub cron: String,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl Schedule {
     pub fn new(cron: impl ToString) -> Self {
-        Self { cron: cron.to_string() }
+        Self { cron: cron.to_string(), ..Default::default() }
     }
 }
 
@@ -853,6 +1063,10 @@ impl Schedule {
 pub struct Watch {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub types: Vec<String>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl Watch {
@@ -876,6 +1090,10 @@ pub struct WorkflowCall {
     /// Secrets for the workflow call
     #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     pub secrets: IndexMap<String, WorkflowCallSecret>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl WorkflowCall {
@@ -900,7 +1118,11 @@ pub struct WorkflowCallInput {
     pub input_type: String,
     /// Default value for the input
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub default: Option<String>,
+    pub default: Option<Value>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 /// Configuration for workflow call output
@@ -913,6 +1135,10 @@ pub struct WorkflowCallOutput {
     /// Value of the output
     #[serde(skip_serializing_if = "String::is_empty")]
     pub value: String,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 /// Configuration for workflow call secret
@@ -924,6 +1150,10 @@ pub struct WorkflowCallSecret {
     pub description: String,
     /// Indicates if the secret is required
     pub required: bool,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 /// Configuration for workflow dispatch events
@@ -935,6 +1165,10 @@ pub struct WorkflowDispatch {
     /// Inputs for the workflow dispatch
     #[serde(skip_serializing_if = "IndexMap::is_empty")]
     pub inputs: IndexMap<String, WorkflowDispatchInput>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 /// Configuration for workflow dispatch input
@@ -953,15 +1187,19 @@ pub struct WorkflowDispatchInput {
     /// Default value for the input
     #[serde(skip_serializing_if = "Option::is_none")]
     #[setters(skip)]
-    pub default: Option<String>,
+    pub default: Option<Value>,
     /// The options of the dropdown list, if the type is a choice
     #[serde(skip_serializing_if = "Vec::is_empty")]
     #[setters(skip)]
     pub options: Vec<String>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl WorkflowDispatchInput {
-    pub fn set_default(self, value: impl Into<String>) -> Self {
+    pub fn set_default(self, value: impl Into<Value>) -> Self {
         Self { default: Some(value.into()), ..self }
     }
 
@@ -999,6 +1237,10 @@ pub struct WorkflowRun {
     /// Filter on specific branch names
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub branches: Vec<String>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 impl WorkflowRun {
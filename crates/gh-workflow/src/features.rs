@@ -0,0 +1,441 @@
+//!
+//! Generates matrices that check a crate with many combinations of its
+//! features, so broken combinations are caught in CI.
+
+use std::collections::{BTreeSet, HashSet};
+
+use derive_setters::Setters;
+use indexmap::IndexMap;
+use serde_json::json;
+
+use crate::workspace::Member;
+use crate::{shell_quote, Cargo, Job, Strategy};
+
+/// The most jobs GitHub runs for a single matrix.
+pub const MAX_MATRIX_JOBS: usize = 256;
+
+/// Which combinations of features are checked, on top of
+/// `--no-default-features` and `--all-features`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Combinations {
+    /// Each feature alone.
+    #[default]
+    Each,
+
+    /// Each feature alone and every pair of features.
+    Pairwise,
+
+    /// Every non-empty subset of the features, up to `max_depth` features.
+    Powerset,
+}
+
+/// A set of features to check the crate with.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FeatureSet {
+    pub no_default_features: bool,
+    pub all_features: bool,
+    pub features: Vec<String>,
+}
+
+impl FeatureSet {
+    /// A short name for the set, for eg: `serde+std`.
+    pub fn name(&self) -> String {
+        match (self.all_features, self.features.is_empty()) {
+            (true, _) => "all-features".to_string(),
+            (false, true) if self.no_default_features => "no-default-features".to_string(),
+            (false, true) => "default".to_string(),
+            (false, false) => self.features.join("+"),
+        }
+    }
+
+    /// The cargo flags that select the set.
+    pub fn flags(&self) -> String {
+        let mut flags = Vec::new();
+        if self.all_features {
+            flags.push("--all-features".to_string());
+        }
+        if self.no_default_features {
+            flags.push("--no-default-features".to_string());
+        }
+        if !self.features.is_empty() {
+            flags.push(format!(
+                "--features {}",
+                shell_quote(&self.features.join(","))
+            ));
+        }
+        flags.join(" ")
+    }
+
+    /// Selects the set on a cargo command.
+    pub fn apply(&self, cargo: Cargo) -> Cargo {
+        cargo
+            .no_default_features(self.no_default_features)
+            .all_features(self.all_features)
+            .features(self.features.clone())
+    }
+}
+
+/// Builds a matrix of feature combinations for a crate.
+#[derive(Clone, Debug, Setters)]
+#[setters(strip_option, into)]
+pub struct FeatureMatrix {
+    /// The features to combine, `default` is never combined.
+    pub features: Vec<String>,
+
+    /// The features enabled by each feature, as in the `[features]` table.
+    /// Combinations are checked for conflicts and duplicates with the
+    /// features they enable.
+    pub enables: IndexMap<String, Vec<String>>,
+
+    /// Which combinations to generate.
+    pub combinations: Combinations,
+
+    /// The largest number of features combined by [`Combinations::Powerset`].
+    pub max_depth: Option<usize>,
+
+    /// Features that are never enabled.
+    pub exclude: Vec<String>,
+
+    /// Groups of features that can't be enabled together.
+    pub mutually_exclusive: Vec<Vec<String>>,
+
+    /// Checks the crate with `--no-default-features`.
+    pub no_default_features: bool,
+
+    /// Checks the crate with `--all-features`. It's skipped when some
+    /// features are excluded or mutually exclusive.
+    pub all_features: bool,
+
+    /// The most feature sets in a single matrix.
+    pub chunk_size: usize,
+}
+
+impl FeatureMatrix {
+    /// Creates a new `FeatureMatrix` combining the given features.
+    pub fn new<T: ToString>(features: impl IntoIterator<Item = T>) -> Self {
+        Self {
+            features: features
+                .into_iter()
+                .map(|feature| feature.to_string())
+                .filter(|feature| feature != "default")
+                .collect(),
+            enables: IndexMap::new(),
+            combinations: Combinations::default(),
+            max_depth: None,
+            exclude: Vec::new(),
+            mutually_exclusive: Vec::new(),
+            no_default_features: true,
+            all_features: true,
+            chunk_size: MAX_MATRIX_JOBS,
+        }
+    }
+
+    /// Creates a new `FeatureMatrix` from the `[features]` table of a
+    /// workspace member.
+    pub fn from_member(member: &Member) -> Self {
+        Self::new(member.features.keys()).enables(member.features.clone())
+    }
+
+    /// Adds a feature that is never enabled.
+    pub fn add_exclude<T: ToString>(mut self, feature: T) -> Self {
+        self.exclude.push(feature.to_string());
+        self
+    }
+
+    /// Adds a group of features that can't be enabled together.
+    pub fn add_mutually_exclusive<T: ToString>(
+        mut self,
+        features: impl IntoIterator<Item = T>,
+    ) -> Self {
+        self.mutually_exclusive
+            .push(features.into_iter().map(|f| f.to_string()).collect());
+        self
+    }
+
+    /// The features enabled by `features`, including themselves and the
+    /// ones they enable in turn. Dependencies enabled with `dep:` or `/` are
+    /// left out.
+    fn expand(&self, features: &[&String]) -> BTreeSet<String> {
+        let mut enabled = BTreeSet::new();
+        let mut stack = features.iter().map(|f| f.as_str()).collect::<Vec<_>>();
+        while let Some(feature) = stack.pop() {
+            if enabled.insert(feature.to_string()) {
+                stack.extend(
+                    self.enables
+                        .get(feature)
+                        .into_iter()
+                        .flatten()
+                        .map(String::as_str)
+                        .filter(|enabled| !enabled.contains([':', '/'])),
+                );
+            }
+        }
+        enabled
+    }
+
+    /// Whether the `enabled` features include an excluded feature or more
+    /// than one feature of a mutually exclusive group.
+    fn conflicts(&self, enabled: &BTreeSet<String>) -> bool {
+        enabled.iter().any(|feature| self.exclude.contains(feature))
+            || self.mutually_exclusive.iter().any(|group| {
+                group
+                    .iter()
+                    .filter(|feature| enabled.contains(*feature))
+                    .count()
+                    > 1
+            })
+    }
+
+    /// Every feature set to check, in a stable order.
+    pub fn feature_sets(&self) -> Vec<FeatureSet> {
+        let features = self
+            .features
+            .iter()
+            .filter(|feature| !self.exclude.contains(feature))
+            .collect::<Vec<_>>();
+
+        let mut sets = Vec::new();
+        if self.no_default_features {
+            sets.push(FeatureSet { no_default_features: true, ..Default::default() });
+        }
+
+        let max_depth = match self.combinations {
+            Combinations::Each => 1,
+            Combinations::Pairwise => 2,
+            Combinations::Powerset => self.max_depth.unwrap_or(features.len()),
+        };
+
+        // Combinations that enable the same features are only checked once.
+        let mut seen = HashSet::new();
+        let mut combination = Vec::new();
+        for depth in 1..=max_depth.min(features.len()) {
+            combine(&features, depth, 0, &mut combination, &mut |combination| {
+                let enabled = self.expand(combination);
+                if !self.conflicts(&enabled) && seen.insert(enabled) {
+                    sets.push(FeatureSet {
+                        no_default_features: true,
+                        all_features: false,
+                        features: combination.iter().map(|f| f.to_string()).collect(),
+                    });
+                }
+            });
+        }
+
+        let has_exclusions =
+            !self.exclude.is_empty() || self.mutually_exclusive.iter().any(|group| group.len() > 1);
+        if self.all_features && !has_exclusions {
+            sets.push(FeatureSet { all_features: true, ..Default::default() });
+        }
+
+        sets
+    }
+
+    /// One `strategy.matrix` per chunk of at most `chunk_size` feature sets.
+    /// Every entry has a `name` and the cargo `flags` that select it.
+    pub fn strategies(&self) -> Vec<Strategy> {
+        let entries = self
+            .feature_sets()
+            .iter()
+            .map(|set| json!({ "name": set.name(), "flags": set.flags() }))
+            .collect::<Vec<_>>();
+
+        entries
+            .chunks(self.chunk_size.clamp(1, MAX_MATRIX_JOBS))
+            .map(|chunk| {
+                Strategy::default()
+                    .fail_fast(false)
+                    .matrix(json!({ "include": chunk }))
+            })
+            .collect()
+    }
+
+    /// One job per matrix chunk, each running `cargo` with the flags of the
+    /// matrix entry. The jobs are keyed by `id`, suffixed with the chunk
+    /// number when there are several.
+    pub fn jobs<T: ToString>(&self, id: T, job: Job, cargo: Cargo) -> IndexMap<String, Job> {
+        let strategies = self.strategies();
+        let count = strategies.len();
+        strategies
+            .into_iter()
+            .enumerate()
+            .map(|(index, strategy)| {
+                let id = if count == 1 {
+                    id.to_string()
+                } else {
+                    format!("{}-{}", id.to_string(), index + 1)
+                };
+                let name = format!(
+                    "{} (${{{{ matrix.name }}}})",
+                    job.name.clone().unwrap_or_else(|| id.clone())
+                );
+                let job = job
+                    .clone()
+                    .name(name)
+                    .strategy(strategy)
+                    .add_step(cargo.clone().add_args("${{ matrix.flags }}"));
+                (id, job)
+            })
+            .collect()
+    }
+}
+
+/// Calls `f` with every combination of `depth` items of `items`, starting at
+/// `start`.
+fn combine<'a>(
+    items: &[&'a String],
+    depth: usize,
+    start: usize,
+    combination: &mut Vec<&'a String>,
+    f: &mut impl FnMut(&[&'a String]),
+) {
+    if combination.len() == depth {
+        f(combination);
+        return;
+    }
+    for index in start..items.len() {
+        combination.push(items[index]);
+        combine(items, depth, index + 1, combination, f);
+        combination.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_snapshot;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::Workflow;
+
+    fn names(matrix: &FeatureMatrix) -> Vec<String> {
+        matrix.feature_sets().iter().map(FeatureSet::name).collect()
+    }
+
+    #[test]
+    fn test_each_feature() {
+        let matrix = FeatureMatrix::new(["default", "std", "serde", "yaml"]);
+        assert_eq!(
+            names(&matrix),
+            [
+                "no-default-features",
+                "std",
+                "serde",
+                "yaml",
+                "all-features"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pairwise_with_exclusions() {
+        let matrix = FeatureMatrix::new(["std", "tokio", "async-std", "nightly"])
+            .combinations(Combinations::Pairwise)
+            .add_exclude("nightly")
+            .add_mutually_exclusive(["tokio", "async-std"]);
+
+        assert_eq!(
+            names(&matrix),
+            [
+                "no-default-features",
+                "std",
+                "tokio",
+                "async-std",
+                "std+tokio",
+                "std+async-std",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_enabled_features() {
+        let matrix = FeatureMatrix::new(["tokio", "async-std", "full", "rt"])
+            .combinations(Combinations::Pairwise)
+            .add_mutually_exclusive(["tokio", "async-std"])
+            .enables(IndexMap::from([
+                (
+                    "full".to_string(),
+                    vec!["tokio".to_string(), "async-std".to_string()],
+                ),
+                (
+                    "tokio".to_string(),
+                    vec!["rt".to_string(), "dep:tokio".to_string()],
+                ),
+            ]));
+
+        assert_eq!(
+            names(&matrix),
+            [
+                "no-default-features",
+                "tokio",
+                "async-std",
+                "rt",
+                "async-std+rt"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_powerset() {
+        let matrix = FeatureMatrix::new(["a", "b", "c"]).combinations(Combinations::Powerset);
+        assert_eq!(
+            names(&matrix),
+            [
+                "no-default-features",
+                "a",
+                "b",
+                "c",
+                "a+b",
+                "a+c",
+                "b+c",
+                "a+b+c",
+                "all-features"
+            ]
+        );
+
+        let matrix = matrix.max_depth(2usize).all_features(false);
+        assert_eq!(names(&matrix).len(), 7);
+    }
+
+    #[test]
+    fn test_flags() {
+        let set = FeatureSet {
+            no_default_features: true,
+            all_features: false,
+            features: vec!["serde".to_string(), "std".to_string()],
+        };
+        assert_eq!(set.flags(), "--no-default-features --features serde,std");
+    }
+
+    #[test]
+    fn test_chunks() {
+        let features = (0..10).map(|i| format!("f{i}")).collect::<Vec<_>>();
+        let matrix = FeatureMatrix::new(features).combinations(Combinations::Powerset);
+
+        // 1024 subsets, `--no-default-features` included, plus `--all-features`.
+        let strategies = matrix.strategies();
+        assert_eq!(strategies.len(), 5);
+        for strategy in &strategies {
+            let include = strategy.matrix.as_ref().unwrap()["include"]
+                .as_array()
+                .unwrap()
+                .len();
+            assert!(include <= MAX_MATRIX_JOBS);
+        }
+    }
+
+    #[test]
+    fn test_jobs() {
+        let jobs = FeatureMatrix::new(["std", "serde"]).jobs(
+            "features",
+            Job::new("Check Features").add_step(crate::Step::checkout()),
+            Cargo::new("check").add_package("gh-workflow"),
+        );
+
+        let workflow = jobs
+            .into_iter()
+            .fold(Workflow::new("Features"), |workflow, (id, job)| {
+                workflow.add_job(id, job)
+            });
+        assert_snapshot!(workflow.to_string().unwrap());
+    }
+}
@@ -0,0 +1,519 @@
+//!
+//! Formatting hints and the YAML emitter used to write workflow files.
+//!
+//! Workflows are written in a fixed, canonical style that doesn't depend on
+//! the YAML library used for serialization:
+//!
+//! - Mappings are indented by two spaces per level and keep the field order of
+//!   the model.
+//! - Sequences are indented under their key, with each item starting with `- `.
+//!   The first key of a mapping item shares the line with the `- `.
+//! - Empty mappings and sequences are written as `{}` and `[]`.
+//! - Strings are written plain unless a YAML 1.2 parser would read them
+//!   differently, for eg: `"true"`, `"1.0"`, `"a: b"` or `" padded"`. When
+//!   quoting is needed they are double-quoted with JSON escapes.
+//! - Strings spanning multiple lines are written as literal blocks (`|` when
+//!   they end with a newline and `|-` otherwise), falling back to a
+//!   double-quoted string when a literal block can't represent them.
+//! - Comments are written on their own line above the node they describe.
+//! - The output ends with a single newline.
+
+use derive_setters::Setters;
+use indexmap::IndexMap;
+use serde_yml::{Mapping, Number, Sequence, Value};
+
+use crate::error::{Error, Result};
+use crate::Workflow;
+
+/// Controls how a workflow is laid out when it is written as YAML.
+#[derive(Debug, Setters, Clone, Default, PartialEq, Eq)]
+#[setters(strip_option, into)]
+pub struct Format {
+    /// How strings spanning multiple lines, such as `run` scripts, are
+    /// written.
+    pub multiline: Multiline,
+
+    /// How mapping keys are quoted.
+    pub key_quote: Quote,
+}
+
+/// The style used for strings that span multiple lines.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Multiline {
+    /// A literal block scalar (`|`), which keeps the text as written.
+    #[default]
+    Literal,
+
+    /// A double-quoted scalar with `\n` escapes.
+    Quoted,
+}
+
+/// The quoting applied to a scalar.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Quote {
+    /// Quote only when the scalar would otherwise be read differently.
+    #[default]
+    Minimal,
+
+    /// Always use single quotes.
+    Single,
+
+    /// Always use double quotes.
+    Double,
+}
+
+/// Writes `workflow` as YAML, including the comments attached to the
+/// workflow, its jobs and their steps.
+pub(crate) fn to_yaml(workflow: &Workflow) -> Result<String> {
+    let value = serde_yml::to_value(workflow).map_err(|e| Error::yaml(e, workflow.name.clone()))?;
+
+    let mut comments = IndexMap::new();
+    if let Some(comment) = &workflow.comment {
+        comments.insert(String::new(), comment.clone());
+    }
+    for (id, job) in workflow.jobs.iter().flat_map(|jobs| jobs.0.iter()) {
+        if let Some(comment) = &job.comment {
+            comments.insert(format!("jobs.{id}"), comment.clone());
+        }
+        for (i, step) in job.steps.iter().flatten().enumerate() {
+            if let Some(comment) = &step.comment {
+                comments.insert(format!("jobs.{id}.steps[{i}]"), comment.clone());
+            }
+        }
+    }
+
+    let mut emitter = Emitter { format: &workflow.format, comments, out: String::new() };
+    emitter.comment("", 0);
+    match &value {
+        Value::Mapping(mapping) if !mapping.is_empty() => emitter.mapping(mapping, 0, "", false),
+        value => emitter.node(value, 0),
+    }
+
+    Ok(emitter.out)
+}
+
+struct Emitter<'a> {
+    format: &'a Format,
+    comments: IndexMap<String, String>,
+    out: String,
+}
+
+impl Emitter<'_> {
+    fn comment(&mut self, path: &str, indent: usize) {
+        if let Some(comment) = self.comments.get(path) {
+            for line in comment.lines() {
+                self.out.push_str(&" ".repeat(indent));
+                if line.is_empty() {
+                    self.out.push_str("#\n");
+                } else {
+                    self.out.push_str(&format!("# {line}\n"));
+                }
+            }
+        }
+    }
+
+    /// Writes the entries of `mapping`. When `inline` is set the first entry
+    /// continues the current line, for eg: after a sequence's `- `.
+    fn mapping(&mut self, mapping: &Mapping, indent: usize, path: &str, inline: bool) {
+        for (i, (key, value)) in mapping.iter().enumerate() {
+            let path = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{path}.{key}")
+            };
+
+            if !(inline && i == 0) {
+                self.comment(&path, indent);
+                self.out.push_str(&" ".repeat(indent));
+            }
+
+            let key = self.key(key);
+            self.out.push_str(&key);
+            self.out.push(':');
+            self.value(value, indent, &path);
+        }
+    }
+
+    fn sequence(&mut self, sequence: &Sequence, indent: usize, path: &str) {
+        for (i, item) in sequence.iter().enumerate() {
+            let path = format!("{path}[{i}]");
+            self.comment(&path, indent);
+            self.out.push_str(&" ".repeat(indent));
+            self.out.push('-');
+
+            match item {
+                Value::Mapping(mapping) if !mapping.is_empty() => {
+                    self.out.push(' ');
+                    self.mapping(mapping, indent + 2, &path, true);
+                }
+                item => self.value(item, indent, &path),
+            }
+        }
+    }
+
+    /// Writes a value that follows a `key:` or `-` on the current line.
+    fn value(&mut self, value: &Value, indent: usize, path: &str) {
+        match value {
+            Value::Mapping(mapping) if !mapping.is_empty() => {
+                self.out.push('\n');
+                self.mapping(mapping, indent + 2, path, false);
+            }
+            Value::Sequence(sequence) if !sequence.is_empty() => {
+                self.out.push('\n');
+                self.sequence(sequence, indent + 2, path);
+            }
+            Value::Tagged(tagged) => {
+                self.out.push(' ');
+                self.out.push_str(tagged.tag().as_ref());
+                self.value(tagged.value(), indent, path);
+            }
+            value => {
+                self.out.push(' ');
+                self.node(value, indent);
+            }
+        }
+    }
+
+    /// Writes a scalar, or an empty collection, in place.
+    fn node(&mut self, value: &Value, indent: usize) {
+        match value {
+            Value::Mapping(mapping) if mapping.is_empty() => self.out.push_str("{}\n"),
+            Value::Sequence(sequence) if sequence.is_empty() => self.out.push_str("[]\n"),
+            Value::String(text) if text.contains('\n') => self.multiline(text, indent),
+            value => {
+                self.out.push_str(&scalar(value));
+                self.out.push('\n');
+            }
+        }
+    }
+
+    fn multiline(&mut self, text: &str, indent: usize) {
+        let body = text.strip_suffix('\n').unwrap_or(text);
+        let literal = self.format.multiline == Multiline::Literal
+            && !body.starts_with(' ')
+            && !body.ends_with('\n')
+            && !body.contains('\r');
+
+        if !literal {
+            self.out.push_str(&double_quoted(text));
+            self.out.push('\n');
+            return;
+        }
+
+        self.out.push_str(if body.len() == text.len() {
+            "|-\n"
+        } else {
+            "|\n"
+        });
+        for line in body.split('\n') {
+            if !line.is_empty() {
+                self.out.push_str(&" ".repeat(indent + 2));
+                self.out.push_str(line);
+            }
+            self.out.push('\n');
+        }
+    }
+
+    fn key(&self, key: &str) -> String {
+        match self.format.key_quote {
+            Quote::Minimal => string(key),
+            Quote::Single => format!("'{}'", key.replace('\'', "''")),
+            Quote::Double => double_quoted(key),
+        }
+    }
+}
+
+/// Writes a single-line scalar.
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(value) => value.to_string(),
+        Value::Number(Number::Integer(value)) => value.to_string(),
+        Value::Number(Number::Float(value)) => {
+            if value.is_nan() {
+                ".nan".to_string()
+            } else if value.is_infinite() {
+                if value.is_sign_positive() {
+                    ".inf"
+                } else {
+                    "-.inf"
+                }
+                .to_string()
+            } else if value.fract() == 0.0 && value.abs() < 1e16 {
+                format!("{value:.1}")
+            } else {
+                value.to_string()
+            }
+        }
+        Value::String(text) => string(text),
+        Value::Tagged(tagged) => format!("{} {}", tagged.tag(), scalar(tagged.value())),
+        Value::Mapping(_) | Value::Sequence(_) => double_quoted(&value.to_string()),
+    }
+}
+
+/// Writes a string plain when that is unambiguous and double-quoted
+/// otherwise.
+fn string(text: &str) -> String {
+    if is_plain_safe(text) {
+        text.to_string()
+    } else {
+        double_quoted(text)
+    }
+}
+
+/// Checks if `text` reads back as the same string when written as a plain
+/// scalar in block context.
+fn is_plain_safe(text: &str) -> bool {
+    let Some(first) = text.chars().next() else {
+        return false;
+    };
+
+    if text.starts_with(char::is_whitespace)
+        || text.ends_with(char::is_whitespace)
+        || text.chars().any(|c| c.is_control())
+        || text.ends_with(':')
+        || text.contains(": ")
+        || text.contains(" #")
+        || text.starts_with("---")
+        || text.starts_with("...")
+        || resolves_to_non_string(text)
+    {
+        return false;
+    }
+
+    match first {
+        '#' | ',' | '[' | ']' | '{' | '}' | '&' | '*' | '!' | '|' | '>' | '\'' | '"' | '%'
+        | '@' | '`' => false,
+        // These are only indicators when followed by a space, or on their own.
+        '-' | '?' | ':' => text.chars().nth(1).is_some_and(|c| !c.is_whitespace()),
+        _ => true,
+    }
+}
+
+/// Checks if a YAML 1.2 core schema parser reads `text` as a null, boolean or
+/// number instead of a string.
+fn resolves_to_non_string(text: &str) -> bool {
+    const KEYWORDS: &[&str] = &[
+        "~", "null", "Null", "NULL", "true", "True", "TRUE", "false", "False", "FALSE", ".inf",
+        ".Inf", ".INF", "+.inf", "+.Inf", "+.INF", "-.inf", "-.Inf", "-.INF", ".nan", ".NaN",
+        ".NAN",
+    ];
+
+    if KEYWORDS.contains(&text) {
+        return true;
+    }
+
+    if let Some(hex) = text.strip_prefix("0x") {
+        return !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit());
+    }
+
+    if let Some(octal) = text.strip_prefix("0o") {
+        return !octal.is_empty() && octal.chars().all(|c| ('0'..='7').contains(&c));
+    }
+
+    // `[-+]?(\.[0-9]+|[0-9]+(\.[0-9]*)?)([eE][-+]?[0-9]+)?`
+    let number = text.strip_prefix(['-', '+']).unwrap_or(text);
+    let (mantissa, exponent) = match number.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (number, None),
+    };
+    let (integer, fraction) = match mantissa.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (mantissa, None),
+    };
+
+    let digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    let valid_mantissa = match fraction {
+        Some(fraction) => {
+            digits(integer) && digits(fraction) && !(integer.is_empty() && fraction.is_empty())
+        }
+        None => !integer.is_empty() && digits(integer),
+    };
+    let valid_exponent = exponent.is_none_or(|exponent| {
+        let exponent = exponent.strip_prefix(['-', '+']).unwrap_or(exponent);
+        !exponent.is_empty() && digits(exponent)
+    });
+
+    valid_mantissa && valid_exponent
+}
+
+/// A JSON string is also a valid double-quoted YAML scalar.
+fn double_quoted(text: &str) -> String {
+    serde_json::to_string(text).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{Event, Job, Push, Step, Workflow};
+
+    fn workflow() -> Workflow {
+        Workflow::new("CI")
+            .comment("Runs the test-suite on every push.")
+            .on(Event::default().push(Push::default().add_branch("main")))
+            .add_job(
+                "test",
+                Job::new("Test")
+                    .comment("Keep in sync with the release job.")
+                    .add_step(
+                        Step::checkout().comment("Pinned to the v4.2.2 tag.\nUpdated by renovate."),
+                    )
+                    .add_step(Step::new("Test").run("cargo build\ncargo test\n")),
+            )
+    }
+
+    #[test]
+    fn test_comments() {
+        let actual = workflow().to_string().unwrap();
+        let expected = r#"# Runs the test-suite on every push.
+name: CI
+on:
+  push:
+    branches:
+      - main
+jobs:
+  # Keep in sync with the release job.
+  test:
+    name: Test
+    runs-on: ubuntu-latest
+    steps:
+      # Pinned to the v4.2.2 tag.
+      # Updated by renovate.
+      - name: Checkout Code
+        uses: actions/checkout@v7
+      - name: Test
+        run: |
+          cargo build
+          cargo test
+"#;
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_quoted_multiline_and_keys() {
+        let format = Format::default()
+            .multiline(Multiline::Quoted)
+            .key_quote(Quote::Single);
+        let actual = Workflow::new("CI")
+            .format(format)
+            .add_job(
+                "test",
+                Job::new("Test").add_step(Step::new("Test").run("a\nb")),
+            )
+            .to_string()
+            .unwrap();
+        let expected = r#"'name': CI
+'jobs':
+  'test':
+    'name': Test
+    'runs-on': ubuntu-latest
+    'steps':
+      - 'name': Test
+        'run': "a\nb"
+"#;
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_literal_falls_back_to_quoted() {
+        let actual = Workflow::new("CI")
+            .add_job(
+                "test",
+                Job::new("Test").add_step(Step::new("Test").run("  indented\nscript")),
+            )
+            .to_string()
+            .unwrap();
+
+        assert!(actual.contains(r#"run: "  indented\nscript""#));
+    }
+
+    #[test]
+    fn test_string_quoting() {
+        let cases = [
+            ("stable", "stable"),
+            ("-Dwarnings", "-Dwarnings"),
+            ("-- -D warnings", "-- -D warnings"),
+            ("${{ secrets.GITHUB_TOKEN }}", "${{ secrets.GITHUB_TOKEN }}"),
+            ("v*", "v*"),
+            ("a#b", "a#b"),
+            ("on", "on"),
+            ("", r#""""#),
+            ("true", r#""true""#),
+            ("NULL", r#""NULL""#),
+            ("~", r#""~""#),
+            ("3", r#""3""#),
+            ("1.0", r#""1.0""#),
+            (".5", r#"".5""#),
+            ("-1e3", r#""-1e3""#),
+            ("0x1F", r#""0x1F""#),
+            (".inf", r#"".inf""#),
+            ("-", r#""-""#),
+            ("- a", r#""- a""#),
+            ("a: b", r#""a: b""#),
+            ("a #b", r#""a #b""#),
+            ("key:", r#""key:""#),
+            (" padded", r#"" padded""#),
+            ("*alias", r#""*alias""#),
+            ("'quoted'", r#""'quoted'""#),
+            ("tab\there", r#""tab\there""#),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(string(input), expected, "quoting {input:?}");
+        }
+    }
+
+    #[test]
+    fn test_non_string_scalars() {
+        assert_eq!(scalar(&Value::Null), "null");
+        assert_eq!(scalar(&Value::Bool(true)), "true");
+        assert_eq!(scalar(&Value::Number(Number::Integer(-7))), "-7");
+        assert_eq!(scalar(&Value::Number(Number::Float(2.0))), "2.0");
+        assert_eq!(scalar(&Value::Number(Number::Float(0.25))), "0.25");
+        assert_eq!(scalar(&Value::Number(Number::Float(f64::NAN))), ".nan");
+    }
+
+    #[test]
+    fn test_empty_workflow() {
+        assert_eq!(Workflow::default().to_string().unwrap(), "{}\n");
+    }
+
+    #[test]
+    fn test_canonical_style() {
+        let workflow = Workflow::new("Canonical")
+            .comment("Golden test for the canonical YAML style.")
+            .add_env(("RUSTFLAGS", "-Dwarnings"))
+            .add_env(("EMPTY", ""))
+            .add_env(("VERSION", "1.0"))
+            .on(Event::default()
+                .push(Push::default().add_branch("main").add_tag("v*"))
+                .workflow_dispatch(crate::WorkflowDispatch::default()))
+            .add_job(
+                "build",
+                Job::new("Build")
+                    .comment("Builds every crate.")
+                    .runs_on(serde_json::json!(["self-hosted", "linux"]))
+                    .strategy(crate::Strategy::default().matrix(serde_json::json!({
+                        "rust": ["stable", "1.75"],
+                        "include": [{ "rust": "nightly", "experimental": true }]
+                    })))
+                    .timeout_minutes(30u32)
+                    .add_step(Step::checkout())
+                    .add_step(
+                        Step::new("Test")
+                            .run("cargo test -- --nocapture\ncargo test --doc\n")
+                            .comment("Doc-tests run separately."),
+                    )
+                    .add_step(
+                        Step::new("Check")
+                            .run("cargo check")
+                            .if_condition(crate::Expression::new("github.event_name == 'push'"))
+                            .continue_on_error(true),
+                    ),
+            );
+
+        insta::assert_snapshot!(workflow.to_string().unwrap());
+    }
+}
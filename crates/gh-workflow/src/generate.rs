@@ -1,7 +1,7 @@
 //! This module provides functionality to customize generation of the GitHub
 //! Actions workflow files.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use derive_setters::Setters;
@@ -14,40 +14,51 @@ use crate::Workflow;
 pub struct Generate {
     workflow: Workflow,
     name: String,
+
+    /// Validates the workflow against the GitHub workflow JSON schema before
+    /// it is written. Requires the `schema` feature.
+    #[cfg(feature = "schema")]
+    validate_schema: bool,
 }
 
 impl Generate {
     pub fn new(workflow: Workflow) -> Self {
-        Self { workflow, name: "ci.yml".to_string() }
+        Self {
+            workflow,
+            name: "ci.yml".to_string(),
+            #[cfg(feature = "schema")]
+            validate_schema: false,
+        }
     }
 
-    fn check_file(&self, path: &PathBuf, content: &str) -> Result<()> {
+    fn check_file(&self, path: &Path, content: &str) -> Result<()> {
         if let Ok(prev) = std::fs::read_to_string(path) {
             if content != prev {
-                Err(Error::OutdatedWorkflow)
+                Err(Error::OutdatedWorkflow {
+                    path: path.to_path_buf(),
+                    diff: diff(&prev, content),
+                })
             } else {
                 Ok(())
             }
         } else {
-            Err(Error::MissingWorkflowFile(path.clone()))
+            Err(Error::MissingWorkflowFile(path.to_path_buf()))
         }
     }
 
     pub fn generate(&self) -> Result<()> {
         let comment = "# @generated by gh-workflow (Rust) - do not edit by hand.";
 
-        let root_dir = String::from_utf8(
-            Command::new("git")
-                .args(["rev-parse", "--show-toplevel"])
-                .output()?
-                .stdout,
-        )?;
-
-        let path = PathBuf::from(root_dir.trim())
+        let path = root_dir()?
             .join(".github")
             .join("workflows")
             .join(self.name.as_str());
 
+        #[cfg(feature = "schema")]
+        if self.validate_schema {
+            self.workflow.validate_schema()?;
+        }
+
         let content = format!("{}\n{}", comment, self.workflow.to_string()?);
 
         let result = self.check_file(&path, &content);
@@ -60,16 +71,17 @@ impl Generate {
                     println!("Workflow file is up-to-date: {}", path.display());
                     Ok(())
                 }
-                Err(Error::OutdatedWorkflow) => {
-                    std::fs::write(path.clone(), content)?;
+                Err(Error::OutdatedWorkflow { .. }) => {
+                    std::fs::write(&path, content).map_err(|e| Error::io(e, &path))?;
                     println!("Updated workflow file: {}", path.display());
                     Ok(())
                 }
                 Err(Error::MissingWorkflowFile(path)) => {
-                    std::fs::create_dir_all(path.parent().ok_or(Error::IO(
-                        std::io::Error::other("Invalid parent dir(s) path"),
-                    ))?)?;
-                    std::fs::write(path.clone(), content)?;
+                    let parent = path.parent().ok_or_else(|| {
+                        Error::io(std::io::Error::other("Invalid parent dir(s) path"), &path)
+                    })?;
+                    std::fs::create_dir_all(parent).map_err(|e| Error::io(e, parent))?;
+                    std::fs::write(&path, content).map_err(|e| Error::io(e, &path))?;
                     println!("Generated workflow file: {}", path.display());
                     Ok(())
                 }
@@ -78,3 +90,119 @@ impl Generate {
         }
     }
 }
+
+/// Finds the root directory of the current git repository.
+pub(crate) fn root_dir() -> Result<PathBuf> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .map_err(|e| Error::GitDiscovery {
+            reason: "failed to run `git rev-parse --show-toplevel`".to_string(),
+            source: Some(Box::new(e)),
+        })?;
+
+    if !output.status.success() {
+        return Err(Error::GitDiscovery {
+            reason: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            source: None,
+        });
+    }
+
+    let root_dir = String::from_utf8(output.stdout).map_err(|e| Error::GitDiscovery {
+        reason: "`git rev-parse` returned a non UTF-8 path".to_string(),
+        source: Some(Box::new(e)),
+    })?;
+
+    Ok(PathBuf::from(root_dir.trim()))
+}
+
+/// Produces a line diff from `old` to `new`. Removed lines are prefixed with
+/// `-`, added lines with `+` and up to two unchanged lines of context are kept
+/// around every change.
+fn diff(old: &str, new: &str) -> String {
+    const CONTEXT: usize = 2;
+
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Longest common subsequence table, computed from the end of both inputs.
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(('-', old[i]));
+            i += 1;
+        } else {
+            lines.push(('+', new[j]));
+            j += 1;
+        }
+    }
+
+    let changed: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, (tag, _))| *tag != ' ')
+        .map(|(index, _)| index)
+        .collect();
+
+    let mut output = Vec::new();
+    let mut last: Option<usize> = None;
+    for (index, (tag, line)) in lines.iter().enumerate() {
+        let near_change = changed
+            .iter()
+            .any(|c| index + CONTEXT >= *c && index <= c + CONTEXT);
+        if !near_change {
+            continue;
+        }
+        if last.is_some_and(|last| index > last + 1) {
+            output.push("...".to_string());
+        }
+        output.push(format!("{tag}{line}"));
+        last = Some(index);
+    }
+
+    output.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_diff_identical() {
+        assert_eq!(diff("a\nb\n", "a\nb\n"), "");
+    }
+
+    #[test]
+    fn test_diff_with_context() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9";
+        let new = "1\n2\n3\n4\nfive\n6\n7\n8\n9";
+
+        assert_eq!(diff(old, new), " 3\n 4\n-5\n+five\n 6\n 7");
+    }
+
+    #[test]
+    fn test_diff_separate_hunks() {
+        let old = "a\n1\n2\n3\n4\n5\n6\nb";
+        let new = "A\n1\n2\n3\n4\n5\n6\nB";
+
+        assert_eq!(diff(old, new), "-a\n+A\n 1\n 2\n...\n 5\n 6\n-b\n+B");
+    }
+}
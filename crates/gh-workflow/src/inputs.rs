@@ -0,0 +1,143 @@
+//!
+//! Typed inputs for `workflow_dispatch` and `workflow_call` workflows. The
+//! traits are usually implemented with `#[derive(WorkflowInputs)]` and
+//! `#[derive(InputChoice)]`.
+
+use serde_json::Value;
+
+use crate::ctx::Context;
+use crate::{Input, WorkflowCall, WorkflowDispatch};
+
+/// A type that can be used as the value of a workflow input.
+pub trait WorkflowInput {
+    /// The `type` of the input, for eg: `string`, `boolean` or `choice`.
+    const TYPE: &'static str;
+
+    /// Whether the input must be provided. Optional types override this.
+    const REQUIRED: bool = true;
+
+    /// The options of the input, if its type is a `choice`.
+    const OPTIONS: &'static [&'static str] = &[];
+
+    /// The options of the input as owned strings.
+    fn options() -> Vec<String> {
+        Self::OPTIONS
+            .iter()
+            .map(|option| option.to_string())
+            .collect()
+    }
+
+    /// The value passed to the input in a `with:` block.
+    fn to_value(&self) -> Option<Value>;
+}
+
+/// A set of typed workflow inputs.
+pub trait WorkflowInputs {
+    /// The inputs declared as a `workflow_dispatch` trigger.
+    fn workflow_dispatch() -> WorkflowDispatch;
+
+    /// The inputs declared as a `workflow_call` trigger.
+    fn workflow_call() -> WorkflowCall;
+
+    /// The `with:` block that calls a reusable workflow with these inputs.
+    fn to_input(&self) -> Input;
+
+    /// The `inputs` context of a workflow using these inputs.
+    fn context() -> Context<Self>
+    where
+        Self: Sized,
+    {
+        Context::<Self>::inputs()
+    }
+}
+
+/// Returns the `type` to use for a `workflow_call` input, which doesn't
+/// support choices.
+#[doc(hidden)]
+pub fn workflow_call_type(input_type: &str) -> &str {
+    match input_type {
+        "choice" | "environment" => "string",
+        input_type => input_type,
+    }
+}
+
+/// Checks at compile time that the `default` of an input matches its type:
+/// `kind` is the type of the literal, one of `string`, `boolean` or
+/// `number`, and the default of a `choice` must be one of its options.
+#[doc(hidden)]
+pub const fn is_valid_default(input_type: &str, options: &[&str], kind: &str, value: &str) -> bool {
+    if str_eq(input_type, "choice") {
+        if !str_eq(kind, "string") {
+            return false;
+        }
+        let mut i = 0;
+        while i < options.len() {
+            if str_eq(options[i], value) {
+                return true;
+            }
+            i += 1;
+        }
+        false
+    } else if str_eq(input_type, "environment") {
+        str_eq(kind, "string")
+    } else {
+        str_eq(input_type, kind)
+    }
+}
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+impl WorkflowInput for String {
+    const TYPE: &'static str = "string";
+
+    fn to_value(&self) -> Option<Value> {
+        Some(Value::from(self.as_str()))
+    }
+}
+
+impl WorkflowInput for bool {
+    const TYPE: &'static str = "boolean";
+
+    fn to_value(&self) -> Option<Value> {
+        Some(Value::from(*self))
+    }
+}
+
+macro_rules! number_input {
+    ($($ty:ty),*) => {
+        $(
+            impl WorkflowInput for $ty {
+                const TYPE: &'static str = "number";
+
+                fn to_value(&self) -> Option<Value> {
+                    Some(Value::from(*self))
+                }
+            }
+        )*
+    };
+}
+
+number_input!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+impl<T: WorkflowInput> WorkflowInput for Option<T> {
+    const TYPE: &'static str = T::TYPE;
+    const REQUIRED: bool = false;
+    const OPTIONS: &'static [&'static str] = T::OPTIONS;
+
+    fn to_value(&self) -> Option<Value> {
+        self.as_ref().and_then(T::to_value)
+    }
+}
@@ -29,6 +29,37 @@ where
     }
 }
 
+impl RunsOn {
+    /// Whether the job runs on a Windows runner, `None` when the runner is
+    /// chosen by an expression.
+    pub(crate) fn is_windows(&self) -> Option<bool> {
+        let labels = match &self.0 {
+            Value::String(label) => vec![label.as_str()],
+            Value::Array(labels) => labels.iter().filter_map(Value::as_str).collect(),
+            Value::Object(group) => group
+                .get("labels")
+                .into_iter()
+                .flat_map(|labels| match labels {
+                    Value::Array(labels) => {
+                        labels.iter().filter_map(Value::as_str).collect::<Vec<_>>()
+                    }
+                    label => label.as_str().into_iter().collect(),
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        if labels.iter().any(|label| label.contains("${{")) {
+            return None;
+        }
+        Some(
+            labels
+                .iter()
+                .any(|label| label.to_lowercase().starts_with("windows")),
+        )
+    }
+}
+
 /// Represents a job in the workflow.
 /// Field order matches GitHub Actions YAML structure for better readability.
 #[derive(Debug, Setters, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -77,6 +108,14 @@ pub struct Job {
     pub artifacts: Option<Artifacts>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub with: Option<Input>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
+
+    /// A comment written above the job.
+    #[serde(skip)]
+    pub comment: Option<String>,
 }
 
 impl Default for Job {
@@ -104,6 +143,8 @@ impl Default for Job {
             retry: None,
             artifacts: None,
             with: None,
+            extra: IndexMap::new(),
+            comment: None,
         }
     }
 }
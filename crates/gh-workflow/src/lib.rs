@@ -1,18 +1,30 @@
 extern crate proc_macro;
 
 mod artifacts;
+pub mod cache;
 mod cargo;
+pub mod checkout;
 mod concurrency;
 mod container;
+pub mod cross;
 mod defaults;
 mod env;
 mod environment;
 pub mod error;
 mod event;
 mod expression;
+pub mod features;
+#[cfg(test)]
+mod fixture;
+mod format;
+mod inputs;
 mod job;
+mod lower;
 mod permissions;
+#[cfg(feature = "schema")]
+mod schema;
 mod secrets;
+mod services;
 mod step;
 mod strategy;
 
@@ -21,6 +33,7 @@ pub mod generate;
 mod rust_flag;
 pub mod toolchain;
 pub(crate) mod workflow;
+pub mod workspace;
 
 pub use artifacts::*;
 pub use cargo::*;
@@ -31,18 +44,39 @@ pub use env::*;
 pub use environment::*;
 pub use event::*;
 pub use expression::*;
+pub use format::*;
+pub use gh_workflow_macros::{action, InputChoice, WorkflowInputs};
+pub use inputs::*;
 pub use job::*;
 pub use permissions::*;
 pub use rust_flag::*;
 pub use secrets::*;
+pub use services::*;
 pub use step::*;
 pub use strategy::*;
 pub use workflow::*;
 
+#[doc(hidden)]
+pub mod __private {
+    pub use indexmap::IndexMap;
+    pub use serde_json::Value;
+}
+
 pub(crate) fn is_default<T: Default + PartialEq>(value: &T) -> bool {
     *value == T::default()
 }
 
+/// Merges the unmodelled keys of `right` into `left`, keeping the values
+/// already present in `left`.
+pub(crate) fn merge_extra(
+    left: &mut indexmap::IndexMap<String, serde_json::Value>,
+    right: indexmap::IndexMap<String, serde_json::Value>,
+) {
+    for (key, value) in right {
+        left.entry(key).or_insert(value);
+    }
+}
+
 mod private {
     pub trait Sealed {}
 }
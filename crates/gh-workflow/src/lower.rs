@@ -0,0 +1,790 @@
+//!
+//! Lowering of the keys this crate models on top of GitHub Actions into
+//! constructs GitHub understands. Lowering runs on a copy of the workflow
+//! whenever it is written or validated, so the builders keep the original
+//! high-level values.
+
+use indexmap::{IndexMap, IndexSet};
+
+use crate::error::{Diagnostic, Error, Result};
+use crate::workspace::matches;
+use crate::{
+    Artifact, Artifacts, Container, Expression, Job, RetryStrategy, RunsOn, StepValue, Workflow,
+};
+
+/// Returns a copy of `workflow` with every high-level key lowered, failing
+/// with [`Error::Validation`] when a key can't be lowered.
+pub(crate) fn lower(workflow: &Workflow) -> Result<Workflow> {
+    let mut lowered = workflow.clone();
+    let mut diagnostics = Vec::new();
+    artifacts(&mut lowered, &mut diagnostics);
+    retry(&mut lowered, &mut diagnostics);
+    containers(&mut lowered);
+
+    if diagnostics.is_empty() {
+        Ok(lowered)
+    } else {
+        Err(Error::Validation { workflow: workflow.name.clone(), diagnostics })
+    }
+}
+
+/// Renders the typed docker options of job containers and services into
+/// their `options`.
+fn containers(workflow: &mut Workflow) {
+    let Some(jobs) = workflow.jobs.as_mut() else {
+        return;
+    };
+
+    for job in jobs.0.values_mut() {
+        let services = job
+            .services
+            .iter_mut()
+            .flat_map(|services| services.values_mut());
+        for container in job.container.iter_mut().chain(services) {
+            lower_container(container);
+        }
+    }
+}
+
+fn lower_container(container: &mut Container) {
+    container.options = container.docker_options();
+    container.health_check = None;
+    container.network = None;
+    container.cpus = None;
+    container.memory = None;
+    container.entrypoint = None;
+}
+
+/// Replaces the `artifacts` of jobs and steps with upload and download
+/// steps, and makes every job that downloads an artifact depend on the jobs
+/// that upload it.
+fn artifacts(workflow: &mut Workflow, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(jobs) = workflow.jobs.as_mut() else {
+        return;
+    };
+
+    // Jobs that call a reusable workflow can't have steps.
+    for (id, job) in jobs.0.iter() {
+        let has_artifacts =
+            job.artifacts.is_some() || job.steps.iter().flatten().any(|s| s.artifacts.is_some());
+        if job.uses.is_some() && has_artifacts {
+            diagnostics.push(Diagnostic::new(
+                format!("jobs.{id}.artifacts"),
+                "a job that calls a reusable workflow can't upload or download artifacts",
+            ));
+        }
+    }
+
+    let uploads: Vec<(String, IndexSet<String>)> = jobs
+        .0
+        .iter()
+        .map(|(id, job)| (id.clone(), artifact_names(job, |a| &a.upload)))
+        .collect();
+
+    for (id, job) in jobs.0.iter_mut() {
+        if job.uses.is_some() {
+            continue;
+        }
+
+        for download in artifacts_of(job, |a| &a.download) {
+            for (producer, _) in uploads.iter().filter(|(producer, names)| {
+                producer != id && names.iter().any(|name| download.selects(name))
+            }) {
+                let needs = job.needs.get_or_insert_with(Vec::new);
+                if !needs.contains(producer) {
+                    needs.push(producer.clone());
+                }
+            }
+        }
+
+        // The job downloads go after the leading checkouts, a checkout
+        // cleans the workspace and would delete them.
+        let artifacts = job.artifacts.take().unwrap_or_default();
+        let job_steps = job.steps.take().unwrap_or_default();
+        let checkouts = job_steps
+            .iter()
+            .take_while(|step| is_checkout(step))
+            .count();
+        let mut steps = Vec::new();
+        for (i, mut step) in job_steps.into_iter().enumerate() {
+            if i == checkouts {
+                steps.extend(download_steps(&artifacts));
+            }
+            let artifacts = step.artifacts.take().unwrap_or_default();
+            steps.extend(download_steps(&artifacts));
+            steps.push(step);
+            steps.extend(upload_steps(&artifacts));
+        }
+        if checkouts == steps.len() {
+            steps.extend(download_steps(&artifacts));
+        }
+        steps.extend(upload_steps(&artifacts));
+
+        if !steps.is_empty() {
+            job.steps = Some(steps);
+        }
+    }
+
+    if let Some(cycle) = needs_cycle(&jobs.0) {
+        diagnostics.push(Diagnostic::new(
+            format!("jobs.{}.needs", cycle[0]),
+            format!("the jobs depend on each other: {}", cycle.join(" -> ")),
+        ));
+    }
+}
+
+/// Whether `step` runs `actions/checkout`.
+fn is_checkout(step: &StepValue) -> bool {
+    step.uses
+        .as_deref()
+        .is_some_and(|uses| uses.starts_with("actions/checkout@"))
+}
+
+impl Artifact {
+    /// Whether downloading this artifact downloads the one named `name`.
+    fn selects(&self, name: &str) -> bool {
+        if self.merge_multiple == Some(true) {
+            matches(&self.name, name)
+        } else {
+            self.name == name
+        }
+    }
+}
+
+/// Collects the artifacts selected by `select` on the job and its steps.
+fn artifacts_of(job: &Job, select: fn(&Artifacts) -> &Option<Vec<Artifact>>) -> Vec<Artifact> {
+    job.artifacts
+        .iter()
+        .chain(
+            job.steps
+                .iter()
+                .flatten()
+                .filter_map(|s| s.artifacts.as_ref()),
+        )
+        .flat_map(|artifacts| select(artifacts).iter().flatten())
+        .cloned()
+        .collect()
+}
+
+/// Collects the names of the artifacts selected by `select` on the job and
+/// its steps.
+fn artifact_names(job: &Job, select: fn(&Artifacts) -> &Option<Vec<Artifact>>) -> IndexSet<String> {
+    artifacts_of(job, select)
+        .into_iter()
+        .map(|artifact| artifact.name)
+        .collect()
+}
+
+/// Finds a cycle in the `needs` of `jobs`, returned as the ids of the jobs
+/// in the cycle with the first one repeated at the end.
+fn needs_cycle(jobs: &IndexMap<String, Job>) -> Option<Vec<String>> {
+    fn visit<'a>(
+        id: &'a str,
+        jobs: &'a IndexMap<String, Job>,
+        path: &mut Vec<&'a str>,
+        done: &mut IndexSet<&'a str>,
+    ) -> Option<Vec<String>> {
+        if let Some(start) = path.iter().position(|visited| *visited == id) {
+            let mut cycle = path[start..]
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>();
+            cycle.push(id.to_string());
+            return Some(cycle);
+        }
+        if done.contains(id) {
+            return None;
+        }
+
+        path.push(id);
+        for need in jobs
+            .get(id)
+            .and_then(|job| job.needs.as_ref())
+            .into_iter()
+            .flatten()
+        {
+            if let Some(cycle) = visit(need, jobs, path, done) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        done.insert(id);
+        None
+    }
+
+    let mut done = IndexSet::new();
+    jobs.keys()
+        .find_map(|id| visit(id, jobs, &mut Vec::new(), &mut done))
+}
+
+fn download_steps(artifacts: &Artifacts) -> impl Iterator<Item = StepValue> + '_ {
+    artifacts
+        .download
+        .iter()
+        .flatten()
+        .map(|artifact| artifact.download_step().value)
+}
+
+fn upload_steps(artifacts: &Artifacts) -> impl Iterator<Item = StepValue> + '_ {
+    artifacts
+        .upload
+        .iter()
+        .flatten()
+        .map(|artifact| artifact.upload_step().value)
+}
+
+/// Replaces the `retry` of jobs, steps and defaults with steps that retry.
+/// A step's own strategy wins over its job's, which wins over the job's
+/// defaults and then the workflow's defaults.
+fn retry(workflow: &mut Workflow, diagnostics: &mut Vec<Diagnostic>) {
+    let workflow_default = take_default_retry(&mut workflow.defaults);
+    let workflow_shell = default_shell(&workflow.defaults);
+
+    for (job_id, job) in workflow.jobs.iter_mut().flat_map(|jobs| jobs.0.iter_mut()) {
+        let job_default = take_default_retry(&mut job.defaults);
+        let default = job
+            .retry
+            .take()
+            .or(job_default)
+            .or_else(|| workflow_default.clone());
+
+        let Some(steps) = job.steps.take() else {
+            continue;
+        };
+
+        let job_shell = default_shell(&job.defaults).or_else(|| workflow_shell.clone());
+        let runner_shell = match (&job.container, job.runs_on.as_ref().map(RunsOn::is_windows)) {
+            (Some(_), _) => Some("sh".to_string()),
+            (None, Some(Some(true))) => Some("pwsh".to_string()),
+            (None, Some(Some(false)) | None) => Some("bash".to_string()),
+            // The runner is picked by an expression, for eg: a matrix.
+            (None, Some(None)) => None,
+        };
+
+        let mut lowered = Vec::new();
+        for (index, mut step) in steps.iter().cloned().enumerate() {
+            let strategy = step.retry.take().or_else(|| default.clone());
+            let Some((strategy, attempts)) =
+                strategy.and_then(|s| s.max_attempts.filter(|n| *n > 1).map(|n| (s, n)))
+            else {
+                lowered.push(step);
+                continue;
+            };
+
+            let shell = step
+                .shell
+                .clone()
+                .or_else(|| job_shell.clone())
+                .or_else(|| runner_shell.clone());
+            let posix = shell.as_deref().and_then(posix_shell);
+
+            match posix {
+                Some(posix) if step.run.is_some() => {
+                    lowered.push(retry_loop(step, &strategy, attempts, posix));
+                }
+                _ => {
+                    if let Some(id) = &step.id {
+                        if is_referenced(id, index, &steps, job) {
+                            diagnostics.push(Diagnostic::new(
+                                format!("jobs.{job_id}.steps[{index}].retry"),
+                                format!(
+                                    "the step can't be repeated because `steps.{id}` is \
+                                     referenced by the job, it would only refer to the first \
+                                     attempt"
+                                ),
+                            ));
+                        }
+                    }
+                    lowered.extend(repeat(step, index, attempts));
+                }
+            }
+        }
+        job.steps = Some(lowered);
+    }
+}
+
+/// The `defaults.run.shell` of a job or workflow.
+fn default_shell(defaults: &Option<crate::Defaults>) -> Option<String> {
+    defaults.as_ref()?.run.as_ref()?.shell.clone()
+}
+
+/// The POSIX shell that runs scripts for `shell`, if it is `bash` or `sh`,
+/// including custom invocations such as `bash -e {0}`.
+fn posix_shell(shell: &str) -> Option<&'static str> {
+    match shell.split_whitespace().next()? {
+        "bash" => Some("bash -eo pipefail"),
+        "sh" => Some("sh -e"),
+        _ => None,
+    }
+}
+
+/// Whether `steps.<id>` is used anywhere in the job but the step itself.
+fn is_referenced(id: &str, index: usize, steps: &[StepValue], job: &Job) -> bool {
+    let reference = format!("steps.{id}.");
+    let others = steps
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != index)
+        .map(|(_, step)| serde_json::to_string(step).unwrap_or_default());
+    let job = Job { steps: None, ..job.clone() };
+
+    std::iter::once(serde_json::to_string(&job).unwrap_or_default())
+        .chain(others)
+        .any(|value| value.contains(&reference))
+}
+
+/// Removes the retry settings from `defaults`, dropping the defaults
+/// altogether when nothing else is left.
+fn take_default_retry(defaults: &mut Option<crate::Defaults>) -> Option<RetryStrategy> {
+    let retry = defaults.as_mut()?.retry.take().map(RetryStrategy::from);
+    if defaults.as_ref().is_some_and(crate::is_default) {
+        *defaults = None;
+    }
+    retry
+}
+
+/// Wraps the script of a `run` step in a loop that runs it up to `attempts`
+/// times. The loop is POSIX so it runs in both bash and sh, and every
+/// attempt runs in its own `shell`, the way GitHub runs the script, so a
+/// failing command still fails the attempt. When every attempt fails, the
+/// step exits with the status of the last one.
+fn retry_loop(
+    mut step: StepValue,
+    strategy: &RetryStrategy,
+    attempts: u32,
+    shell: &str,
+) -> StepValue {
+    let script = step.run.take().unwrap_or_default();
+    let delay = strategy.delay_seconds.unwrap_or(0);
+
+    // The heredoc ends at the first line equal to the delimiter, so it must
+    // not be a line of the script.
+    let delimiter = (0..)
+        .map(|n| match n {
+            0 => "GH_WORKFLOW_RETRY".to_string(),
+            n => format!("GH_WORKFLOW_RETRY_{n}"),
+        })
+        .find(|delimiter| !script.lines().any(|line| line == delimiter))
+        .unwrap_or_default();
+
+    let mut run = String::new();
+    run.push_str(&format!("gh_workflow_script=$(cat <<'{delimiter}'\n"));
+    run.push_str(script.trim_end_matches('\n'));
+    run.push_str(&format!("\n{delimiter}\n)\n"));
+    if delay > 0 {
+        run.push_str(&format!("delay={delay}\n"));
+    }
+    run.push_str("attempt=1\n");
+    run.push_str(&format!("while [ \"$attempt\" -le {attempts} ]; do\n"));
+    run.push_str(&format!("  if {shell} -c \"$gh_workflow_script\"; then\n"));
+    run.push_str("    exit 0\n");
+    run.push_str("  else\n");
+    run.push_str("    status=$?\n");
+    run.push_str("  fi\n");
+    run.push_str(&format!("  if [ \"$attempt\" -lt {attempts} ]; then\n"));
+    if delay > 0 {
+        run.push_str(&format!(
+            "    echo \"Attempt $attempt of {attempts} failed, retrying in ${{delay}}s\"\n"
+        ));
+        run.push_str("    sleep \"$delay\"\n");
+        if strategy.exponential_backoff.unwrap_or(false) {
+            run.push_str("    delay=$((delay * 2))\n");
+        }
+    } else {
+        run.push_str(&format!(
+            "    echo \"Attempt $attempt of {attempts} failed, retrying\"\n"
+        ));
+    }
+    run.push_str("  fi\n");
+    run.push_str("  attempt=$((attempt + 1))\n");
+    run.push_str("done\n");
+    run.push_str("exit \"$status\"\n");
+
+    step.run = Some(run);
+    step
+}
+
+/// Repeats `step` so that it is attempted up to `attempts` times. Every copy
+/// runs only if the previous attempt failed, and all attempts but the last
+/// continue on error so that the job carries on to the next one.
+fn repeat(mut step: StepValue, index: usize, attempts: u32) -> Vec<StepValue> {
+    let id = step.id.clone().unwrap_or_else(|| format!("retry-{index}"));
+    let condition = step.if_condition.as_ref().map(|cond| {
+        let cond = cond.0.trim();
+        cond.strip_prefix("${{")
+            .and_then(|cond| cond.strip_suffix("}}"))
+            .unwrap_or(cond)
+            .trim()
+            .to_string()
+    });
+    let continue_on_error = step.continue_on_error;
+
+    step.id = Some(id.clone());
+    step.continue_on_error = Some(true);
+
+    let mut steps = vec![step.clone()];
+    let mut previous = id.clone();
+    for attempt in 2..=attempts {
+        let mut copy = step.clone();
+        let failed = format!("steps.{previous}.outcome == 'failure'");
+
+        copy.id = Some(format!("{id}-attempt-{attempt}"));
+        copy.name = step
+            .name
+            .as_ref()
+            .map(|name| format!("{name} (attempt {attempt})"));
+        copy.if_condition = Some(Expression::new(match &condition {
+            Some(condition) => format!("({condition}) && {failed}"),
+            None => failed,
+        }));
+        copy.comment = None;
+        if attempt == attempts {
+            copy.continue_on_error = continue_on_error;
+        }
+
+        previous = copy.id.clone().unwrap_or_default();
+        steps.push(copy);
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_snapshot;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{Defaults, Event, IfNoFilesFound, Push, RetryDefaults, RunDefaults, Step};
+
+    fn workflow() -> Workflow {
+        Workflow::new("CI")
+            .on(Event::default().push(Push::default().add_branch("main")))
+            .add_job(
+                "build",
+                Job::new("Build")
+                    .add_step(
+                        Step::new("Build").run("cargo build --release").artifacts(
+                            Artifacts::default().add_upload(
+                                Artifact::new("binary", "target/release/app")
+                                    .retention_days(7u32)
+                                    .if_no_files_found(IfNoFilesFound::Error),
+                            ),
+                        ),
+                    )
+                    .add_step(Step::new("Docs").run("cargo doc")),
+            )
+            .add_job(
+                "release",
+                Job::new("Release")
+                    .artifacts(
+                        Artifacts::default()
+                            .add_download(Artifact::new("binary", "dist").merge_multiple(true))
+                            .add_upload(Artifact::new("checksums", "dist/*.sha256")),
+                    )
+                    .add_step(Step::new("Checksum").run("sha256sum dist/* > dist/app.sha256")),
+            )
+    }
+
+    #[test]
+    fn test_lower_artifacts() {
+        assert_snapshot!(workflow().to_string().unwrap());
+    }
+
+    #[test]
+    fn test_lower_artifacts_adds_needs() {
+        let workflow = lower(&workflow()).unwrap();
+        let jobs = workflow.jobs.unwrap();
+
+        assert_eq!(jobs.get("build").unwrap().needs, None);
+        assert_eq!(
+            jobs.get("release").unwrap().needs,
+            Some(vec!["build".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_lower_artifacts_pattern_needs() {
+        let workflow = Workflow::new("CI")
+            .add_job(
+                "linux",
+                Job::new("Linux").artifacts(
+                    Artifacts::default().add_upload(Artifact::new("binary-linux", "dist")),
+                ),
+            )
+            .add_job(
+                "macos",
+                Job::new("macOS").artifacts(
+                    Artifacts::default().add_upload(Artifact::new("binary-macos", "dist")),
+                ),
+            )
+            .add_job(
+                "release",
+                Job::new("Release").artifacts(
+                    Artifacts::default()
+                        .add_download(Artifact::new("binary-*", "dist").merge_multiple(true)),
+                ),
+            );
+
+        let jobs = lower(&workflow).unwrap().jobs.unwrap();
+        let release = jobs.get("release").unwrap();
+        assert_eq!(
+            release.needs,
+            Some(vec!["linux".to_string(), "macos".to_string()])
+        );
+
+        let download = &release.steps.as_ref().unwrap()[0];
+        let with = download.with.as_ref().unwrap();
+        assert_eq!(with.0.get("pattern"), Some(&"binary-*".into()));
+        assert_eq!(with.0.get("name"), None);
+    }
+
+    #[test]
+    fn test_lower_artifacts_after_checkout() {
+        let workflow = Workflow::new("CI").add_job(
+            "release",
+            Job::new("Release")
+                .add_step(Step::checkout())
+                .add_step(Step::new("Publish").run("ls dist"))
+                .artifacts(Artifacts::default().add_download(Artifact::new("binary", "dist"))),
+        );
+
+        let jobs = lower(&workflow).unwrap().jobs.unwrap();
+        let names = jobs
+            .get("release")
+            .unwrap()
+            .steps
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|step| step.name.clone().unwrap_or_default())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Checkout Code", "Download binary", "Publish"]);
+    }
+
+    #[test]
+    fn test_lower_artifacts_errors() {
+        let workflow = Workflow::new("CI")
+            .add_job(
+                "build",
+                Job::new("Build")
+                    .add_needs("test")
+                    .artifacts(Artifacts::default().add_upload(Artifact::new("binary", "dist"))),
+            )
+            .add_job(
+                "test",
+                Job::new("Test")
+                    .artifacts(Artifacts::default().add_download(Artifact::new("binary", "dist"))),
+            )
+            .add_job(
+                "deploy",
+                Job::default()
+                    .uses("./.github/workflows/deploy.yml")
+                    .artifacts(Artifacts::default().add_download(Artifact::new("binary", "dist"))),
+            );
+
+        let Err(Error::Validation { diagnostics, .. }) = lower(&workflow) else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic::new(
+                    "jobs.deploy.artifacts",
+                    "a job that calls a reusable workflow can't upload or download artifacts"
+                ),
+                Diagnostic::new(
+                    "jobs.build.needs",
+                    "the jobs depend on each other: build -> test -> build"
+                ),
+            ]
+        );
+    }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn test_lowered_workflow_matches_schema() {
+        assert_eq!(
+            crate::schema::validate(&lower(&workflow()).unwrap()),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_lower_retry() {
+        let workflow = Workflow::new("CI")
+            .on(Event::default().push(Push::default().add_branch("main")))
+            .defaults(Defaults::default().retry(RetryDefaults::default().max_attempts(2u32)))
+            .add_job(
+                "build",
+                Job::new("Build")
+                    .add_step(Step::checkout())
+                    .add_step(
+                        Step::new("Test").run("cargo test\ncargo test --doc").retry(
+                            RetryStrategy::new(3)
+                                .delay_seconds(5u32)
+                                .exponential_backoff(true),
+                        ),
+                    )
+                    .add_step(
+                        Step::new("Lint")
+                            .run("cargo clippy")
+                            .retry(RetryStrategy::new(1)),
+                    ),
+            );
+
+        assert_snapshot!(workflow.to_string().unwrap());
+    }
+
+    #[test]
+    fn test_lower_retry_delimiter() {
+        let workflow = Workflow::new("CI").add_job(
+            "test",
+            Job::new("Test").add_step(
+                Step::new("Test")
+                    .run("cat <<'GH_WORKFLOW_RETRY'\nhello\nGH_WORKFLOW_RETRY")
+                    .retry(RetryStrategy::new(2)),
+            ),
+        );
+
+        let jobs = lower(&workflow).unwrap().jobs.unwrap();
+        let run = jobs.get("test").unwrap().steps.as_ref().unwrap()[0]
+            .run
+            .clone()
+            .unwrap();
+        assert!(run.starts_with(
+            "gh_workflow_script=$(cat <<'GH_WORKFLOW_RETRY_1'\n\
+             cat <<'GH_WORKFLOW_RETRY'\nhello\nGH_WORKFLOW_RETRY\n\
+             GH_WORKFLOW_RETRY_1\n)\n"
+        ));
+    }
+
+    #[test]
+    fn test_lower_retry_shell() {
+        let step = || {
+            Step::new("Test")
+                .run("cargo test")
+                .retry(RetryStrategy::new(2))
+        };
+        let workflow = Workflow::new("CI")
+            .defaults(Defaults::default().run(RunDefaults::default().shell("sh")))
+            .add_job("linux", Job::new("Linux").add_step(step()))
+            .add_job(
+                "windows",
+                Job::new("Windows")
+                    .runs_on("windows-latest")
+                    .add_step(step()),
+            )
+            .add_job(
+                "bash",
+                Job::new("Windows Bash")
+                    .runs_on("windows-latest")
+                    .add_step(step().shell("bash")),
+            )
+            .add_job(
+                "matrix",
+                Job::new("Matrix")
+                    .runs_on("${{ matrix.os }}")
+                    .add_step(step()),
+            );
+
+        let jobs = lower(&workflow).unwrap().jobs.unwrap();
+        let steps = |id: &str| jobs.get(id).unwrap().steps.clone().unwrap();
+
+        // The workflow defaults win over the runner OS.
+        let linux = steps("linux");
+        assert_eq!(linux.len(), 1);
+        assert!(linux[0].run.as_ref().unwrap().contains("if sh -e -c"));
+        assert_eq!(linux[0].shell, None);
+
+        let windows = steps("windows");
+        assert_eq!(windows.len(), 1);
+        assert!(windows[0].run.as_ref().unwrap().contains("if sh -e -c"));
+
+        let bash = steps("bash");
+        assert!(bash[0]
+            .run
+            .as_ref()
+            .unwrap()
+            .contains("if bash -eo pipefail -c"));
+
+        // Without defaults, a runner picked by an expression may run pwsh.
+        let workflow = Workflow::new("CI")
+            .add_job(
+                "windows",
+                Job::new("Windows")
+                    .runs_on("windows-latest")
+                    .add_step(step()),
+            )
+            .add_job(
+                "matrix",
+                Job::new("Matrix")
+                    .runs_on("${{ matrix.os }}")
+                    .add_step(step()),
+            );
+        let jobs = lower(&workflow).unwrap().jobs.unwrap();
+        for id in ["windows", "matrix"] {
+            let steps = jobs.get(id).unwrap().steps.clone().unwrap();
+            assert_eq!(steps.len(), 2);
+            assert_eq!(steps[0].run.as_deref(), Some("cargo test"));
+        }
+    }
+
+    #[test]
+    fn test_lower_retry_referenced_id() {
+        let workflow = Workflow::new("CI").add_job(
+            "build",
+            Job::new("Build")
+                .add_output("version", "${{ steps.version.outputs.version }}")
+                .add_step(
+                    Step::new("Version")
+                        .uses("actions", "version", "v1")
+                        .id("version")
+                        .retry(RetryStrategy::new(2)),
+                ),
+        );
+
+        let Err(Error::Validation { diagnostics, .. }) = lower(&workflow) else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].path, "jobs.build.steps[0].retry");
+    }
+
+    #[test]
+    fn test_lower_retry_precedence() {
+        let workflow = Workflow::new("CI")
+            .defaults(Defaults::default().retry(RetryDefaults::default().max_attempts(2u32)))
+            .add_job(
+                "build",
+                Job::new("Build")
+                    .retry(RetryStrategy::new(3))
+                    .add_step(Step::new("Publish").uses("actions", "publish", "v1")),
+            );
+
+        let workflow = lower(&workflow).unwrap();
+        let steps = workflow
+            .jobs
+            .unwrap()
+            .get("build")
+            .unwrap()
+            .steps
+            .clone()
+            .unwrap();
+        let ids = steps
+            .iter()
+            .map(|s| s.id.clone().unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(workflow.defaults, None);
+        assert_eq!(
+            ids,
+            vec!["retry-0", "retry-0-attempt-2", "retry-0-attempt-3"]
+        );
+        assert_eq!(
+            steps[2].if_condition,
+            Some(Expression::new(
+                "steps.retry-0-attempt-2.outcome == 'failure'"
+            ))
+        );
+    }
+}
@@ -12,6 +12,10 @@ pub struct Permissions {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub actions: Option<Level>,
 
+    /// Permissions for artifact attestations.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attestations: Option<Level>,
+
     /// Permissions for repository contents.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub contents: Option<Level>,
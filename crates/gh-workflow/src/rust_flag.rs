@@ -1,18 +1,34 @@
 //! A type-safe representation of the Rust toolchain.
 
 use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
 use indexmap::IndexMap;
 use serde_json::Value;
 
+use crate::toolchain::Target;
 use crate::Env;
 
-#[derive(Clone)]
+/// Flags passed to `rustc`. Flags combined with `+` are deduplicated when
+/// rendered, and a later flag overrides an earlier one for the same lint,
+/// codegen option or `-Z` option. Options that take several values, like
+/// `-C link-arg`, `-L` and unknown flags, are all kept in order.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum RustFlags {
     Lint(String, Lint),
+    /// `--cfg name` or `--cfg name="value"`.
+    Cfg(String, Option<String>),
+    /// `--cap-lints level`.
+    CapLints(LintLevel),
+    /// `-L [kind=]path`.
+    LibraryPath(String),
+    /// Any other flag with its value, if any, kept as-is, for eg:
+    /// `--extern name=path`.
+    Raw(String, Option<String>),
     Combine(Box<Self>, Box<Self>),
 }
-#[derive(Clone)]
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Lint {
     Allow,
     Warn,
@@ -22,6 +38,48 @@ pub enum Lint {
     Experiment,
 }
 
+/// The level of a lint, as used by `--cap-lints`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+    Forbid,
+}
+
+impl Display for LintLevel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let val = match self {
+            Self::Allow => "allow",
+            Self::Warn => "warn",
+            Self::Deny => "deny",
+            Self::Forbid => "forbid",
+        };
+        write!(f, "{val}")
+    }
+}
+
+impl FromStr for LintLevel {
+    type Err = RustFlagsError;
+
+    fn from_str(level: &str) -> Result<Self, Self::Err> {
+        match level {
+            "allow" => Ok(Self::Allow),
+            "warn" => Ok(Self::Warn),
+            "deny" => Ok(Self::Deny),
+            "forbid" => Ok(Self::Forbid),
+            _ => Err(RustFlagsError {
+                flags: level.to_string(),
+                reason: RustFlagsErrorReason::InvalidLintLevel(level.to_string()),
+            }),
+        }
+    }
+}
+
+/// The `-C` and `-Z` options that may be passed several times, each adding
+/// a value instead of overriding the previous one.
+const MULTI_VALUE_OPTIONS: &[&str] = &["link-arg", "link-args", "llvm-args", "passes"];
+
 impl core::ops::Add for RustFlags {
     type Output = Self;
 
@@ -50,28 +108,457 @@ impl RustFlags {
     pub fn codegen<S: ToString>(name: S) -> Self {
         Self::Lint(name.to_string(), Lint::Codegen)
     }
+
+    /// An unstable `-Z` option, it requires a nightly toolchain.
+    pub fn experiment<S: ToString>(name: S) -> Self {
+        Self::Lint(name.to_string(), Lint::Experiment)
+    }
+
+    pub fn cfg<S: ToString>(name: S) -> Self {
+        Self::Cfg(name.to_string(), None)
+    }
+
+    pub fn cfg_value<S: ToString, V: ToString>(name: S, value: V) -> Self {
+        Self::Cfg(name.to_string(), Some(value.to_string()))
+    }
+
+    pub fn cap_lints(level: LintLevel) -> Self {
+        Self::CapLints(level)
+    }
+
+    /// Adds a library search path, optionally prefixed with its kind, for
+    /// eg: `native=/usr/lib`.
+    pub fn library_path<S: ToString>(path: S) -> Self {
+        Self::LibraryPath(path.to_string())
+    }
+
+    /// The individual flags, deduplicated and with overridden flags
+    /// removed.
+    pub fn flags(&self) -> Vec<Self> {
+        fn flatten(flags: &RustFlags, out: &mut Vec<RustFlags>) {
+            match flags {
+                RustFlags::Combine(lhs, rhs) => {
+                    flatten(lhs, out);
+                    flatten(rhs, out);
+                }
+                flag => out.push(flag.clone()),
+            }
+        }
+
+        let mut all = Vec::new();
+        flatten(self, &mut all);
+
+        let mut flags: Vec<Self> = Vec::new();
+        for flag in all {
+            if let Some(key) = flag.key() {
+                flags.retain(|existing| existing.key().as_ref() != Some(&key));
+            }
+            flags.push(flag);
+        }
+        flags
+    }
+
+    /// Flags with the same key override each other, flags without one are
+    /// always kept.
+    fn key(&self) -> Option<(u8, String)> {
+        /// The name of a `-C` or `-Z` option, without its value.
+        fn option(name: &str) -> Option<String> {
+            let option = name.split_once('=').map_or(name, |(name, _)| name);
+            (!MULTI_VALUE_OPTIONS.contains(&option)).then(|| option.to_string())
+        }
+
+        match self {
+            Self::Lint(name, Lint::Codegen) => Some((0, option(name)?)),
+            Self::Lint(name, Lint::Experiment) => Some((1, option(name)?)),
+            Self::Lint(name, _) => Some((2, name.clone())),
+            Self::Cfg(..) => Some((3, self.args().join(" "))),
+            Self::CapLints(_) => Some((4, String::new())),
+            Self::LibraryPath(_) | Self::Raw(..) | Self::Combine(..) => None,
+        }
+    }
+
+    /// The arguments passed to `rustc`.
+    pub fn args(&self) -> Vec<String> {
+        match self {
+            Self::Lint(name, lint) => vec![match lint {
+                Lint::Allow => format!("-A{name}"),
+                Lint::Warn => format!("-W{name}"),
+                Lint::Deny => format!("-D{name}"),
+                Lint::Forbid => format!("-F{name}"),
+                Lint::Codegen => format!("-C{name}"),
+                Lint::Experiment => format!("-Z{name}"),
+            }],
+            Self::Cfg(name, None) => vec!["--cfg".to_string(), name.clone()],
+            Self::Cfg(name, Some(value)) => {
+                vec!["--cfg".to_string(), format!("{name}=\"{value}\"")]
+            }
+            Self::CapLints(level) => vec!["--cap-lints".to_string(), level.to_string()],
+            Self::LibraryPath(path) => vec![format!("-L{path}")],
+            Self::Raw(flag, value) => std::iter::once(flag).chain(value).cloned().collect(),
+            Self::Combine(..) => self.flags().iter().flat_map(Self::args).collect(),
+        }
+    }
+
+    /// The flags separated by spaces, failing if an argument contains
+    /// whitespace since cargo would split it.
+    fn to_space_separated(&self) -> Result<String, RustFlagsError> {
+        let args = self.args();
+        match args.iter().find(|arg| arg.contains(char::is_whitespace)) {
+            Some(arg) => Err(RustFlagsError {
+                flags: self.to_string(),
+                reason: RustFlagsErrorReason::Whitespace(arg.clone()),
+            }),
+            None => Ok(args.join(" ")),
+        }
+    }
+
+    /// The flags as `RUSTDOCFLAGS`. Fails if an argument contains
+    /// whitespace, see [`RustFlags::to_encoded_env`].
+    pub fn to_rustdoc_env(&self) -> Result<Env, RustFlagsError> {
+        Ok(env("RUSTDOCFLAGS", self.to_space_separated()?))
+    }
+
+    /// The flags as `CARGO_TARGET_<TRIPLE>_RUSTFLAGS`, which only applies
+    /// when building for `target`. Fails if an argument contains whitespace,
+    /// see [`RustFlags::to_encoded_env`].
+    pub fn to_target_env(&self, target: &Target) -> Result<Env, RustFlagsError> {
+        let triple = target.to_string().to_uppercase().replace(['-', '.'], "_");
+        Ok(env(
+            format!("CARGO_TARGET_{triple}_RUSTFLAGS"),
+            self.to_space_separated()?,
+        ))
+    }
+
+    /// The flags as `CARGO_ENCODED_RUSTFLAGS`, separated by `0x1f` so
+    /// arguments may contain spaces.
+    pub fn to_encoded_env(&self) -> Env {
+        env("CARGO_ENCODED_RUSTFLAGS", self.args().join("\u{1f}"))
+    }
 }
 
+fn env<K: ToString>(key: K, value: String) -> Env {
+    let mut env = IndexMap::default();
+    env.insert(key.to_string(), Value::from(value));
+    Env::from(env)
+}
+
+/// Renders the flags separated by spaces. It's ambiguous when an argument
+/// contains whitespace, use [`RustFlags::to_encoded_env`] for those.
 impl Display for RustFlags {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::Lint(name, lint) => match lint {
-                Lint::Allow => write!(f, "-A{name}"),
-                Lint::Warn => write!(f, "-W{name}"),
-                Lint::Deny => write!(f, "-D{name}"),
-                Lint::Forbid => write!(f, "-F{name}"),
-                Lint::Codegen => write!(f, "-C{name}"),
-                Lint::Experiment => write!(f, "-Z{name}"),
-            },
-            Self::Combine(lhs, rhs) => write!(f, "{lhs} {rhs}"),
+        write!(f, "{}", self.args().join(" "))
+    }
+}
+
+/// The error returned when flags can't be parsed or rendered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RustFlagsError {
+    /// The flags that failed to parse or render.
+    pub flags: String,
+    pub reason: RustFlagsErrorReason,
+}
+
+/// Why flags are invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RustFlagsErrorReason {
+    /// The flags string has no flags.
+    Empty,
+    /// A flag is missing its value, for eg: a trailing `-C`.
+    MissingValue(String),
+    InvalidLintLevel(String),
+    /// A `--cfg` value opens a quote it doesn't close, for eg: `foo="bar`.
+    UnbalancedQuote(String),
+    /// An argument contains whitespace, which space separated flags can't
+    /// represent.
+    Whitespace(String),
+}
+
+impl Display for RustFlagsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid flags `{}`: ", self.flags)?;
+        match &self.reason {
+            RustFlagsErrorReason::Empty => write!(f, "no flags to parse"),
+            RustFlagsErrorReason::MissingValue(flag) => write!(f, "`{flag}` is missing its value"),
+            RustFlagsErrorReason::InvalidLintLevel(level) => write!(
+                f,
+                "unknown lint level `{level}`, expected `allow`, `warn`, `deny` or `forbid`"
+            ),
+            RustFlagsErrorReason::UnbalancedQuote(cfg) => write!(
+                f,
+                "`{cfg}` has an unclosed quote, flags are split on whitespace so quoted values \
+                 can't contain any"
+            ),
+            RustFlagsErrorReason::Whitespace(arg) => write!(
+                f,
+                "`{arg}` contains whitespace, which only `CARGO_ENCODED_RUSTFLAGS` can pass"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RustFlagsError {}
+
+impl FromStr for RustFlags {
+    type Err = RustFlagsError;
+
+    /// Parses a `RUSTFLAGS` string, for eg: `-D warnings --cfg tokio_unstable`.
+    /// Like cargo, it splits on whitespace, so quoted values can't contain
+    /// any.
+    fn from_str(flags: &str) -> Result<Self, Self::Err> {
+        let error =
+            |reason: RustFlagsErrorReason| RustFlagsError { flags: flags.to_string(), reason };
+        let mut tokens = flags.split_whitespace().peekable();
+        let mut parsed: Option<Self> = None;
+
+        while let Some(token) = tokens.next() {
+            // Splits `-Dwarnings`, `-D warnings`, `--deny=warnings` and
+            // `--deny warnings` into the flag and its value.
+            let mut value = |flag: &str, attached: &str| -> Result<String, Self::Err> {
+                if !attached.is_empty() {
+                    return Ok(attached.trim_start_matches('=').to_string());
+                }
+                tokens
+                    .next()
+                    .map(str::to_string)
+                    .ok_or_else(|| error(RustFlagsErrorReason::MissingValue(flag.to_string())))
+            };
+
+            let (flag, attached) = match token.split_once('=') {
+                Some((flag, _)) if flag.starts_with("--") => (flag, &token[flag.len()..]),
+                _ if token.starts_with("--") => (token, ""),
+                _ if token.len() > 2 && token.starts_with('-') => token.split_at(2),
+                _ => (token, ""),
+            };
+
+            let flag = match flag {
+                "-A" | "--allow" => Self::allow(value(flag, attached)?),
+                "-W" | "--warn" => Self::warn(value(flag, attached)?),
+                "-D" | "--deny" => Self::deny(value(flag, attached)?),
+                "-F" | "--forbid" => Self::forbid(value(flag, attached)?),
+                "-C" | "--codegen" => Self::codegen(value(flag, attached)?),
+                "-Z" => Self::experiment(value(flag, attached)?),
+                "-L" => Self::library_path(value(flag, attached)?),
+                "--cap-lints" => {
+                    let level = value(flag, attached)?;
+                    Self::cap_lints(level.parse().map_err(|e: RustFlagsError| error(e.reason))?)
+                }
+                "--cfg" => {
+                    let cfg = value(flag, attached)?;
+                    match cfg.split_once('=') {
+                        Some((name, value)) => {
+                            let unquoted = value
+                                .strip_prefix('"')
+                                .and_then(|value| value.strip_suffix('"'));
+                            match unquoted {
+                                Some(value) => Self::cfg_value(name, value),
+                                None if !value.contains('"') => Self::cfg_value(name, value),
+                                None => {
+                                    return Err(error(RustFlagsErrorReason::UnbalancedQuote(cfg)))
+                                }
+                            }
+                        }
+                        None => Self::cfg(cfg),
+                    }
+                }
+                // An unknown flag keeps the value that follows it.
+                _ if token.starts_with('-') => {
+                    let value = tokens.next_if(|next| !next.starts_with('-'));
+                    Self::Raw(token.to_string(), value.map(str::to_string))
+                }
+                _ => Self::Raw(token.to_string(), None),
+            };
+
+            parsed = Some(match parsed {
+                Some(parsed) => parsed + flag,
+                None => flag,
+            });
         }
+
+        parsed.ok_or_else(|| error(RustFlagsErrorReason::Empty))
     }
 }
 
+/// The flags as `RUSTFLAGS`, or `CARGO_ENCODED_RUSTFLAGS` when an argument
+/// contains whitespace.
 impl From<RustFlags> for Env {
     fn from(value: RustFlags) -> Self {
-        let mut env = IndexMap::default();
-        env.insert("RUSTFLAGS".to_string(), Value::from(value.to_string()));
-        Self::from(env)
+        match value.to_space_separated() {
+            Ok(flags) => env("RUSTFLAGS", flags),
+            Err(_) => value.to_encoded_env(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let flags = RustFlags::deny("warnings")
+            + RustFlags::codegen("opt-level=3")
+            + RustFlags::cfg("tokio_unstable")
+            + RustFlags::cfg_value("feature", "serde")
+            + RustFlags::cap_lints(LintLevel::Warn)
+            + RustFlags::library_path("native=/usr/lib")
+            + RustFlags::experiment("sanitizer=address");
+
+        assert_eq!(
+            flags.to_string(),
+            "-Dwarnings -Copt-level=3 --cfg tokio_unstable --cfg feature=\"serde\" \
+             --cap-lints warn -Lnative=/usr/lib -Zsanitizer=address"
+        );
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let flags = "-Dwarnings -Copt-level=3 --cfg tokio_unstable --cfg feature=\"serde\" \
+                     --cap-lints warn -Lnative=/usr/lib -Zsanitizer=address -g";
+        assert_eq!(flags.parse::<RustFlags>().unwrap().to_string(), flags);
+    }
+
+    #[test]
+    fn test_parse_separated_values() {
+        let flags = "-D warnings -C target-cpu=native --deny=unsafe_code --cap-lints=allow"
+            .parse::<RustFlags>()
+            .unwrap();
+
+        assert_eq!(
+            flags.flags(),
+            [
+                RustFlags::deny("warnings"),
+                RustFlags::codegen("target-cpu=native"),
+                RustFlags::deny("unsafe_code"),
+                RustFlags::cap_lints(LintLevel::Allow),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "".parse::<RustFlags>().unwrap_err().reason,
+            RustFlagsErrorReason::Empty
+        );
+        assert_eq!(
+            "-D warnings -C".parse::<RustFlags>(),
+            Err(RustFlagsError {
+                flags: "-D warnings -C".to_string(),
+                reason: RustFlagsErrorReason::MissingValue("-C".to_string()),
+            })
+        );
+        assert_eq!(
+            "--cap-lints loud"
+                .parse::<RustFlags>()
+                .unwrap_err()
+                .to_string(),
+            "invalid flags `--cap-lints loud`: unknown lint level `loud`, expected `allow`, \
+             `warn`, `deny` or `forbid`"
+        );
+        assert_eq!(
+            "--cfg foo=\"bar".parse::<RustFlags>().unwrap_err().reason,
+            RustFlagsErrorReason::UnbalancedQuote("foo=\"bar".to_string())
+        );
+    }
+
+    #[test]
+    fn test_overrides() {
+        let flags = RustFlags::deny("warnings")
+            + RustFlags::codegen("opt-level=3")
+            + RustFlags::cfg("tokio_unstable")
+            + RustFlags::allow("warnings")
+            + RustFlags::codegen("opt-level=s")
+            + RustFlags::cfg("tokio_unstable");
+
+        assert_eq!(
+            flags.to_string(),
+            "-Awarnings -Copt-level=s --cfg tokio_unstable"
+        );
+    }
+
+    #[test]
+    fn test_multi_value_options() {
+        let flags = RustFlags::codegen("link-arg=-Xlinker")
+            + RustFlags::codegen("link-arg=foo")
+            + RustFlags::codegen("link-arg=-Xlinker")
+            + RustFlags::codegen("link-arg=bar")
+            + RustFlags::library_path("a")
+            + RustFlags::library_path("b");
+
+        assert_eq!(
+            flags.to_string(),
+            "-Clink-arg=-Xlinker -Clink-arg=foo -Clink-arg=-Xlinker -Clink-arg=bar -La -Lb"
+        );
+    }
+
+    #[test]
+    fn test_parse_raw_values() {
+        let flags = "--extern a --extern b -g --edition=2021"
+            .parse::<RustFlags>()
+            .unwrap();
+
+        assert_eq!(
+            flags.flags(),
+            [
+                RustFlags::Raw("--extern".to_string(), Some("a".to_string())),
+                RustFlags::Raw("--extern".to_string(), Some("b".to_string())),
+                RustFlags::Raw("-g".to_string(), None),
+                RustFlags::Raw("--edition=2021".to_string(), None),
+            ]
+        );
+        assert_eq!(flags.to_string(), "--extern a --extern b -g --edition=2021");
+    }
+
+    #[test]
+    fn test_env() {
+        let flags = RustFlags::deny("warnings") + RustFlags::cfg_value("foo", "bar");
+
+        assert_eq!(
+            flags.to_rustdoc_env().unwrap(),
+            Env::new("RUSTDOCFLAGS", "-Dwarnings --cfg foo=\"bar\"")
+        );
+        assert_eq!(
+            flags
+                .to_target_env(&Target::X86_64_UNKNOWN_LINUX_GNU)
+                .unwrap(),
+            Env::new(
+                "CARGO_TARGET_X86_64_UNKNOWN_LINUX_GNU_RUSTFLAGS",
+                "-Dwarnings --cfg foo=\"bar\""
+            )
+        );
+        assert_eq!(
+            Env::from(flags),
+            Env::new("RUSTFLAGS", "-Dwarnings --cfg foo=\"bar\"")
+        );
+    }
+
+    #[test]
+    fn test_env_whitespace() {
+        let flags = RustFlags::deny("warnings") + RustFlags::cfg_value("foo", "a b");
+
+        assert_eq!(
+            flags.to_rustdoc_env(),
+            Err(RustFlagsError {
+                flags: "-Dwarnings --cfg foo=\"a b\"".to_string(),
+                reason: RustFlagsErrorReason::Whitespace("foo=\"a b\"".to_string()),
+            })
+        );
+        assert!(flags
+            .to_target_env(&Target::X86_64_UNKNOWN_LINUX_GNU)
+            .is_err());
+
+        let encoded = Env::new(
+            "CARGO_ENCODED_RUSTFLAGS",
+            "-Dwarnings\u{1f}--cfg\u{1f}foo=\"a b\"",
+        );
+        assert_eq!(flags.to_encoded_env(), encoded);
+        assert_eq!(Env::from(flags), encoded);
+
+        assert_eq!(
+            "--cfg foo=\"a b\"".parse::<RustFlags>().unwrap_err().reason,
+            RustFlagsErrorReason::UnbalancedQuote("foo=\"a".to_string())
+        );
     }
 }
@@ -0,0 +1,112 @@
+//!
+//! Validation of workflows against the GitHub workflow JSON schema. The
+//! schema is vendored in `schema/github-workflow.json` so validation works
+//! offline. Until `scripts/sync-workflow-schema.sh` is run the file is a
+//! hand-written stand-in, see `schema/README.md`.
+
+use std::sync::OnceLock;
+
+use jsonschema::paths::LocationSegment;
+use jsonschema::Validator;
+
+use crate::error::Diagnostic;
+use crate::Workflow;
+
+const SCHEMA: &str = include_str!("../schema/github-workflow.json");
+
+fn validator() -> &'static Validator {
+    static VALIDATOR: OnceLock<Validator> = OnceLock::new();
+    VALIDATOR.get_or_init(|| {
+        let schema = serde_json::from_str(SCHEMA).expect("vendored schema is valid JSON");
+        jsonschema::draft7::new(&schema).expect("vendored schema is a valid draft-07 schema")
+    })
+}
+
+/// Validates `workflow` against the schema and returns a diagnostic for
+/// every violation.
+pub(crate) fn validate(workflow: &Workflow) -> Vec<Diagnostic> {
+    let instance = match serde_json::to_value(workflow) {
+        Ok(instance) => instance,
+        Err(e) => return vec![Diagnostic::new("", e)],
+    };
+
+    let mut diagnostics: Vec<Diagnostic> = validator()
+        .iter_errors(&instance)
+        .map(|error| {
+            let mut path = String::new();
+            for segment in error.instance_path().iter() {
+                match segment {
+                    LocationSegment::Index(index) => path.push_str(&format!("[{index}]")),
+                    LocationSegment::Property(property) if path.is_empty() => {
+                        path.push_str(&property)
+                    }
+                    LocationSegment::Property(property) => {
+                        path.push('.');
+                        path.push_str(&property);
+                    }
+                }
+            }
+            Diagnostic::new(path, error)
+        })
+        .collect();
+
+    diagnostics.sort_by(|a, b| a.path.cmp(&b.path));
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{Artifacts, Concurrency, Event, Expression, Job, Push, RetryStrategy, Step};
+
+    fn workflow(job: Job) -> Workflow {
+        Workflow::new("CI")
+            .on(Event::default().push(Push::default().add_branch("main")))
+            .add_job("build", job)
+    }
+
+    #[test]
+    fn test_valid_workflow() {
+        let job = Job::new("Build").add_step(Step::new("Test").run("cargo test"));
+        assert_eq!(validate(&workflow(job)), vec![]);
+    }
+
+    #[test]
+    fn test_rejects_unmodelled_github_keys() {
+        let job = Job::new("Build")
+            .concurrency(Concurrency::new(Expression::new("build")).limit(1u32))
+            .retry(RetryStrategy::new(3))
+            .add_step(
+                Step::new("Test")
+                    .run("cargo test")
+                    .artifacts(Artifacts::default()),
+            );
+
+        let paths = validate(&workflow(job))
+            .into_iter()
+            .map(|d| d.path)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            paths,
+            vec![
+                "jobs.build",
+                "jobs.build.concurrency",
+                "jobs.build.steps[0]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rejects_missing_trigger() {
+        let diagnostics = validate(&Workflow::new("CI").add_job("build", Job::new("Build")));
+        let messages = diagnostics
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(messages, vec![r#""on" is a required property"#]);
+    }
+}
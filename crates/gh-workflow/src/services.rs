@@ -0,0 +1,179 @@
+//!
+//! Presets for service containers commonly used by integration tests. Every
+//! preset exposes its port without a host mapping, so the runner picks a free
+//! host port; use `port()` to refer to it from the job.
+
+use std::time::Duration;
+
+use derive_setters::Setters;
+
+use crate::ctx::{self, Context};
+use crate::{Container, Env, HealthCheck};
+
+/// The host port mapped to `port` of the service `name` of the current job.
+fn mapped_port(name: &str, port: u16) -> Context<String> {
+    Context::<ctx::Job>::job()
+        .services()
+        .get(name)
+        .ports()
+        .get(port.to_string())
+}
+
+/// The health check timings shared by the presets.
+fn health_check(cmd: &str) -> HealthCheck {
+    HealthCheck::new(cmd)
+        .interval(Duration::from_secs(10))
+        .timeout(Duration::from_secs(5))
+        .retries(5u32)
+}
+
+/// A PostgreSQL service container.
+#[derive(Debug, Setters, Clone, PartialEq, Eq)]
+#[setters(strip_option, into)]
+pub struct Postgres {
+    /// The tag of the `postgres` image.
+    pub version: String,
+    pub user: String,
+    pub password: String,
+    pub database: String,
+}
+
+impl Default for Postgres {
+    fn default() -> Self {
+        Self {
+            version: "17".to_string(),
+            user: "postgres".to_string(),
+            password: "postgres".to_string(),
+            database: "postgres".to_string(),
+        }
+    }
+}
+
+impl Postgres {
+    pub const PORT: u16 = 5432;
+
+    /// The host port of the service added to the job as `name`.
+    pub fn port(name: &str) -> Context<String> {
+        mapped_port(name, Self::PORT)
+    }
+}
+
+impl From<Postgres> for Container {
+    fn from(value: Postgres) -> Self {
+        Self::new(format!("postgres:{}", value.version))
+            .env(
+                Env::new("POSTGRES_USER", value.user)
+                    .add("POSTGRES_PASSWORD", value.password)
+                    .add("POSTGRES_DB", value.database),
+            )
+            .add_port(Postgres::PORT)
+            .health_check(health_check("pg_isready"))
+    }
+}
+
+/// A Redis service container.
+#[derive(Debug, Setters, Clone, PartialEq, Eq)]
+#[setters(strip_option, into)]
+pub struct Redis {
+    /// The tag of the `redis` image.
+    pub version: String,
+}
+
+impl Default for Redis {
+    fn default() -> Self {
+        Self { version: "7".to_string() }
+    }
+}
+
+impl Redis {
+    pub const PORT: u16 = 6379;
+
+    /// The host port of the service added to the job as `name`.
+    pub fn port(name: &str) -> Context<String> {
+        mapped_port(name, Self::PORT)
+    }
+}
+
+impl From<Redis> for Container {
+    fn from(value: Redis) -> Self {
+        Self::new(format!("redis:{}", value.version))
+            .add_port(Redis::PORT)
+            .health_check(health_check("redis-cli ping"))
+    }
+}
+
+/// A MySQL service container.
+#[derive(Debug, Setters, Clone, PartialEq, Eq)]
+#[setters(strip_option, into)]
+pub struct MySql {
+    /// The tag of the `mysql` image.
+    pub version: String,
+    pub root_password: String,
+    pub database: String,
+}
+
+impl Default for MySql {
+    fn default() -> Self {
+        Self {
+            version: "8".to_string(),
+            root_password: "root".to_string(),
+            database: "test".to_string(),
+        }
+    }
+}
+
+impl MySql {
+    pub const PORT: u16 = 3306;
+
+    /// The host port of the service added to the job as `name`.
+    pub fn port(name: &str) -> Context<String> {
+        mapped_port(name, Self::PORT)
+    }
+}
+
+impl From<MySql> for Container {
+    fn from(value: MySql) -> Self {
+        Self::new(format!("mysql:{}", value.version))
+            .env(
+                Env::new("MYSQL_ROOT_PASSWORD", value.root_password)
+                    .add("MYSQL_DATABASE", value.database),
+            )
+            .add_port(MySql::PORT)
+            .health_check(health_check("mysqladmin ping"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_snapshot;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{Job, Step, Workflow};
+
+    #[test]
+    fn test_service_presets() {
+        let job = Job::new("Integration")
+            .add_service("postgres", Postgres::default())
+            .add_service("redis", Redis::default().version("7.4"))
+            .add_service("mysql", MySql::default())
+            .add_step(
+                Step::new("Test")
+                    .run("cargo test")
+                    .add_env(("PGPORT", Postgres::port("postgres").to_string())),
+            );
+
+        assert_snapshot!(Workflow::default()
+            .add_job("test", job)
+            .to_string()
+            .unwrap());
+    }
+
+    #[test]
+    fn test_port_context() {
+        assert_eq!(
+            Redis::port("cache").to_string(),
+            "${{ job.services.cache.ports['6379'] }}"
+        );
+    }
+}
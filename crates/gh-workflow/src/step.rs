@@ -7,7 +7,8 @@ use merge::Merge;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::toolchain::{Abi, Arch, Component, System, Target, Toolchain, Vendor, Version};
+use crate::checkout::Checkout;
+use crate::toolchain::{Component, Target, Toolchain, Version};
 use crate::{private, Artifacts, Env, Expression, RetryStrategy};
 
 /// Represents a step in the workflow.
@@ -174,6 +175,16 @@ pub struct StepValue {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[merge(strategy = merge::option::overwrite_none)]
     pub artifacts: Option<Artifacts>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    #[merge(strategy = crate::merge_extra)]
+    pub extra: IndexMap<String, Value>,
+
+    /// A comment written above the step.
+    #[serde(skip)]
+    #[merge(strategy = merge::option::overwrite_none)]
+    pub comment: Option<String>,
 }
 
 impl StepValue {
@@ -238,9 +249,10 @@ impl Step<()> {
 
 /// Represents a step that uses an action.
 impl Step<Use> {
-    /// Creates a step pointing to the default GitHub's Checkout Action.
+    /// Creates a step pointing to the default GitHub's Checkout Action. Use
+    /// [`Checkout`] to configure its inputs.
     pub fn checkout() -> Self {
-        Step::new("Checkout Code").uses("actions", "checkout", "v7")
+        Checkout::default().into()
     }
 
     /// Adds a new input to the step.
@@ -302,8 +314,8 @@ impl Step<Toolchain> {
         self
     }
 
-    pub fn target(mut self, arch: Arch, vendor: Vendor, system: System, abi: Option<Abi>) -> Self {
-        self.marker.target = Some(Target { arch, vendor, system, abi });
+    pub fn add_target(mut self, target: Target) -> Self {
+        self.marker.targets.push(target);
         self
     }
 }
@@ -2,138 +2,554 @@
 //! Docs: <https://github.com/actions-rust-lang/setup-rust-toolchain>
 
 use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 use derive_setters::Setters;
+use serde::Deserialize;
 
+use crate::error::{Error, Result};
+use crate::generate::root_dir;
+use crate::workspace::read_manifest;
 use crate::{private, Input, RustFlags, Step, Use};
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Version {
     Stable,
+    Beta,
     Nightly,
     Custom((u64, u64, u64)),
+    /// Any other toolchain rustup understands, for eg: `nightly-2024-05-01`
+    /// or `1.75`.
+    Channel(String),
 }
 
 impl Display for Version {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         match self {
             Self::Stable => write!(f, "stable"),
+            Self::Beta => write!(f, "beta"),
             Self::Nightly => write!(f, "nightly"),
             Self::Custom(s) => write!(f, "{}.{}.{}", s.0, s.1, s.2),
+            Self::Channel(channel) => write!(f, "{channel}"),
         }
     }
 }
 
+impl FromStr for Version {
+    type Err = std::convert::Infallible;
+
+    fn from_str(version: &str) -> std::result::Result<Self, Self::Err> {
+        let numbers = version
+            .split('.')
+            .map(str::parse::<u64>)
+            .collect::<std::result::Result<Vec<_>, _>>();
+
+        Ok(match (version, numbers.as_deref()) {
+            ("stable", _) => Self::Stable,
+            ("beta", _) => Self::Beta,
+            ("nightly", _) => Self::Nightly,
+            (_, Ok(&[major, minor, patch])) => Self::new(major, minor, patch),
+            (channel, _) => Self::Channel(channel.to_string()),
+        })
+    }
+}
+
 impl Version {
     pub fn new(major: u64, minor: u64, patch: u64) -> Self {
         Self::Custom((major, minor, patch))
     }
+
+    /// The minimum supported Rust version of the package being built, read
+    /// from its `Cargo.toml`.
+    pub fn msrv() -> Result<Self> {
+        let dir = std::env::var_os("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .map_or_else(std::env::current_dir, Ok)?;
+        Self::msrv_in(dir)
+    }
+
+    /// The minimum supported Rust version of the package in `dir`. It's read
+    /// from `package.rust-version`, falling back to
+    /// `workspace.package.rust-version` of the enclosing workspace.
+    pub fn msrv_in<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let path = dir.as_ref().join("Cargo.toml");
+        let manifest = read_manifest(&path)?;
+
+        let package = manifest
+            .get("package")
+            .and_then(|package| package.get("rust-version"));
+        let inherited = package
+            .and_then(|version| version.get("workspace"))
+            .and_then(toml::Value::as_bool)
+            .unwrap_or(false);
+
+        let version = match package {
+            Some(toml::Value::String(version)) => Some(version.clone()),
+            Some(_) if !inherited => {
+                return Err(Error::manifest(
+                    &path,
+                    "`package.rust-version` must be a string",
+                ))
+            }
+            _ => workspace_rust_version(dir.as_ref(), &manifest)?,
+        };
+
+        let version = version
+            .ok_or_else(|| Error::manifest(&path, "no `rust-version` is set for the package"))?;
+        let Ok(version) = version.parse();
+        Ok(version)
+    }
 }
 
-#[derive(Clone, Debug)]
+/// Finds `workspace.package.rust-version` in the manifest of `dir` or of
+/// the closest workspace above it.
+fn workspace_rust_version(dir: &Path, manifest: &toml::Table) -> Result<Option<String>> {
+    let version = |manifest: &toml::Table| {
+        manifest
+            .get("workspace")
+            .and_then(|workspace| workspace.get("package"))
+            .and_then(|package| package.get("rust-version"))
+            .and_then(toml::Value::as_str)
+            .map(str::to_string)
+    };
+
+    if manifest.contains_key("workspace") {
+        return Ok(version(manifest));
+    }
+
+    for dir in dir.ancestors().skip(1) {
+        let path = dir.join("Cargo.toml");
+        if path.is_file() {
+            let manifest = read_manifest(&path)?;
+            if manifest.contains_key("workspace") {
+                return Ok(version(&manifest));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// A rustup component, see `rustup component list`.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Component {
+    Cargo,
     Clippy,
-    Rustfmt,
+    LlvmTools,
+    LlvmToolsPreview,
+    Miri,
+    RustAnalyzer,
     RustDoc,
+    RustSrc,
+    RustStd,
+    Rustc,
+    RustcCodegenCranelift,
+    RustcDev,
+    Rustfmt,
+}
+
+impl Component {
+    /// Every component rustup knows about.
+    pub const ALL: &'static [Self] = &[
+        Self::Cargo,
+        Self::Clippy,
+        Self::LlvmTools,
+        Self::LlvmToolsPreview,
+        Self::Miri,
+        Self::RustAnalyzer,
+        Self::RustDoc,
+        Self::RustSrc,
+        Self::RustStd,
+        Self::Rustc,
+        Self::RustcCodegenCranelift,
+        Self::RustcDev,
+        Self::Rustfmt,
+    ];
+
+    /// Whether the component is only shipped with the nightly toolchain.
+    pub fn is_nightly_only(&self) -> bool {
+        matches!(self, Self::Miri | Self::RustcCodegenCranelift)
+    }
 }
 
 impl Display for Component {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let val = match self {
+            Self::Cargo => "cargo",
             Self::Clippy => "clippy",
+            Self::LlvmTools => "llvm-tools",
+            Self::LlvmToolsPreview => "llvm-tools-preview",
+            Self::Miri => "miri",
+            Self::RustAnalyzer => "rust-analyzer",
+            Self::RustDoc => "rust-docs",
+            Self::RustSrc => "rust-src",
+            Self::RustStd => "rust-std",
+            Self::Rustc => "rustc",
+            Self::RustcCodegenCranelift => "rustc-codegen-cranelift-preview",
+            Self::RustcDev => "rustc-dev",
             Self::Rustfmt => "rustfmt",
-            Self::RustDoc => "rust-doc",
         };
         write!(f, "{val}")
     }
 }
 
-#[derive(Clone)]
-pub enum Arch {
-    X86_64,
-    Aarch64,
-    Arm,
-    Wasm32,
-}
+impl FromStr for Component {
+    type Err = ComponentError;
 
-impl Display for Arch {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let val = match self {
-            Self::X86_64 => "x86_64",
-            Self::Aarch64 => "aarch64",
-            Self::Arm => "arm",
-            Self::Wasm32 => "wasm32",
-        };
-        write!(f, "{val}")
+    fn from_str(component: &str) -> std::result::Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|known| known.to_string() == component)
+            .cloned()
+            .ok_or_else(|| ComponentError(component.to_string()))
     }
 }
 
-#[derive(Clone)]
-pub enum Vendor {
-    Unknown,
-    Apple,
-    PC,
-}
+/// The error returned when a rustup component isn't known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentError(pub String);
 
-impl Display for Vendor {
+impl Display for ComponentError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let val = match self {
-            Self::Unknown => "unknown",
-            Self::Apple => "apple",
-            Self::PC => "pc",
-        };
-        write!(f, "{val}")
+        write!(f, "unknown rustup component `{}`", self.0)
     }
 }
 
-#[derive(Clone)]
-pub enum System {
-    Unknown,
-    Windows,
-    Linux,
-    Darwin,
+impl std::error::Error for ComponentError {}
+
+/// Declares a component of a target triple along with its spelling.
+macro_rules! triple_part {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident => $value:literal),* $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant),*
+        }
+
+        impl $name {
+            /// Every known value of this component.
+            pub const ALL: &'static [Self] = &[$(Self::$variant),*];
+
+            /// The spelling of this component in a target triple.
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $value),*
+                }
+            }
+
+            fn parse(value: &str) -> Option<Self> {
+                Self::ALL.iter().copied().find(|part| part.as_str() == value)
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.as_str())
+            }
+        }
+    };
 }
 
-impl Display for System {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let val = match self {
-            Self::Unknown => "unknown",
-            Self::Windows => "windows",
-            Self::Linux => "linux",
-            Self::Darwin => "darwin",
-        };
-        write!(f, "{val}")
+triple_part!(
+    /// The CPU architecture of a target.
+    Arch {
+        X86_64 => "x86_64",
+        I586 => "i586",
+        I686 => "i686",
+        Aarch64 => "aarch64",
+        Arm64ec => "arm64ec",
+        Arm => "arm",
+        Armv5te => "armv5te",
+        Armv7 => "armv7",
+        Armv7a => "armv7a",
+        Armv7r => "armv7r",
+        Armebv7r => "armebv7r",
+        Thumbv6m => "thumbv6m",
+        Thumbv7em => "thumbv7em",
+        Thumbv7m => "thumbv7m",
+        Thumbv7neon => "thumbv7neon",
+        Thumbv8mBase => "thumbv8m.base",
+        Thumbv8mMain => "thumbv8m.main",
+        Riscv32i => "riscv32i",
+        Riscv32imac => "riscv32imac",
+        Riscv32imc => "riscv32imc",
+        Riscv32imafc => "riscv32imafc",
+        Riscv64gc => "riscv64gc",
+        Riscv64imac => "riscv64imac",
+        Powerpc => "powerpc",
+        Powerpc64 => "powerpc64",
+        Powerpc64le => "powerpc64le",
+        S390x => "s390x",
+        Sparc64 => "sparc64",
+        Sparcv9 => "sparcv9",
+        Loongarch64 => "loongarch64",
+        Nvptx64 => "nvptx64",
+        Wasm32 => "wasm32",
+        Wasm32v1 => "wasm32v1",
     }
-}
+);
 
-#[derive(Clone)]
-pub enum Abi {
-    Unknown,
-    Gnu,
-    Msvc,
-    Musl,
+triple_part!(
+    /// The vendor of a target, omitted by some triples like
+    /// `aarch64-linux-android`.
+    Vendor {
+        Unknown => "unknown",
+        Apple => "apple",
+        PC => "pc",
+        Sun => "sun",
+        Nvidia => "nvidia",
+        Fortanix => "fortanix",
+    }
+);
+
+triple_part!(
+    /// The operating system of a target.
+    System {
+        Linux => "linux",
+        Windows => "windows",
+        Darwin => "darwin",
+        Ios => "ios",
+        FreeBsd => "freebsd",
+        NetBsd => "netbsd",
+        Illumos => "illumos",
+        Solaris => "solaris",
+        Fuchsia => "fuchsia",
+        Redox => "redox",
+        Uefi => "uefi",
+        Cuda => "cuda",
+        WasiP1 => "wasip1",
+        WasiP2 => "wasip2",
+        Emscripten => "emscripten",
+        None => "none",
+        Unknown => "unknown",
+    }
+);
+
+triple_part!(
+    /// The ABI or environment of a target.
+    Abi {
+        Gnu => "gnu",
+        GnuX32 => "gnux32",
+        GnuLlvm => "gnullvm",
+        GnuEabi => "gnueabi",
+        GnuEabihf => "gnueabihf",
+        Musl => "musl",
+        MuslEabi => "musleabi",
+        MuslEabihf => "musleabihf",
+        Msvc => "msvc",
+        Android => "android",
+        AndroidEabi => "androideabi",
+        Ohos => "ohos",
+        Eabi => "eabi",
+        Eabihf => "eabihf",
+        Elf => "elf",
+        Sgx => "sgx",
+        Sim => "sim",
+        MacAbi => "macabi",
+        SoftFloat => "softfloat",
+        Threads => "threads",
+    }
+);
+
+/// The operating system of a GitHub-hosted runner, as reported by
+/// `runner.os`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RunnerOs {
+    Linux,
+    Windows,
+    MacOs,
 }
 
-impl Display for Abi {
+impl Display for RunnerOs {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let val = match self {
-            Self::Unknown => "unknown",
-            Self::Gnu => "gnu",
-            Self::Msvc => "msvc",
-            Self::Musl => "musl",
+            Self::Linux => "Linux",
+            Self::Windows => "Windows",
+            Self::MacOs => "macOS",
         };
         write!(f, "{val}")
     }
 }
 
-#[derive(Clone, Setters)]
+/// A Rust target triple, for eg: `x86_64-unknown-linux-gnu`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Setters)]
+#[setters(strip_option)]
 pub struct Target {
     pub arch: Arch,
-    pub vendor: Vendor,
+    pub vendor: Option<Vendor>,
     pub system: System,
     pub abi: Option<Abi>,
 }
 
+impl Target {
+    pub const fn new(arch: Arch, vendor: Vendor, system: System, abi: Option<Abi>) -> Self {
+        Self { arch, vendor: Some(vendor), system, abi }
+    }
+
+    pub const X86_64_UNKNOWN_LINUX_GNU: Self =
+        Self::new(Arch::X86_64, Vendor::Unknown, System::Linux, Some(Abi::Gnu));
+    pub const X86_64_UNKNOWN_LINUX_MUSL: Self = Self::new(
+        Arch::X86_64,
+        Vendor::Unknown,
+        System::Linux,
+        Some(Abi::Musl),
+    );
+    pub const AARCH64_UNKNOWN_LINUX_GNU: Self = Self::new(
+        Arch::Aarch64,
+        Vendor::Unknown,
+        System::Linux,
+        Some(Abi::Gnu),
+    );
+    pub const AARCH64_UNKNOWN_LINUX_MUSL: Self = Self::new(
+        Arch::Aarch64,
+        Vendor::Unknown,
+        System::Linux,
+        Some(Abi::Musl),
+    );
+    pub const X86_64_APPLE_DARWIN: Self =
+        Self::new(Arch::X86_64, Vendor::Apple, System::Darwin, None);
+    pub const AARCH64_APPLE_DARWIN: Self =
+        Self::new(Arch::Aarch64, Vendor::Apple, System::Darwin, None);
+    pub const X86_64_PC_WINDOWS_MSVC: Self =
+        Self::new(Arch::X86_64, Vendor::PC, System::Windows, Some(Abi::Msvc));
+    pub const AARCH64_PC_WINDOWS_MSVC: Self =
+        Self::new(Arch::Aarch64, Vendor::PC, System::Windows, Some(Abi::Msvc));
+    pub const I686_PC_WINDOWS_MSVC: Self =
+        Self::new(Arch::I686, Vendor::PC, System::Windows, Some(Abi::Msvc));
+    pub const WASM32_UNKNOWN_UNKNOWN: Self =
+        Self::new(Arch::Wasm32, Vendor::Unknown, System::Unknown, None);
+    pub const WASM32_WASIP1: Self = Self {
+        arch: Arch::Wasm32,
+        vendor: None,
+        system: System::WasiP1,
+        abi: None,
+    };
+    pub const WASM32_WASIP2: Self = Self {
+        arch: Arch::Wasm32,
+        vendor: None,
+        system: System::WasiP2,
+        abi: None,
+    };
+
+    /// The operating system of the runners that build this target without
+    /// cross-compiling, if any.
+    pub fn native_runner_os(&self) -> Option<RunnerOs> {
+        match (self.arch, self.system, self.abi) {
+            (Arch::X86_64 | Arch::Aarch64, System::Linux, Some(Abi::Gnu | Abi::Musl))
+            | (Arch::I686, System::Linux, Some(Abi::Gnu)) => Some(RunnerOs::Linux),
+            (
+                Arch::X86_64 | Arch::Aarch64 | Arch::I686,
+                System::Windows,
+                Some(Abi::Msvc | Abi::Gnu | Abi::GnuLlvm),
+            ) => Some(RunnerOs::Windows),
+            (Arch::X86_64 | Arch::Aarch64, System::Darwin, None) => Some(RunnerOs::MacOs),
+            _ => None,
+        }
+    }
+
+    /// The labels of the GitHub-hosted runners that build this target
+    /// natively. Empty when the target has to be cross-compiled.
+    pub fn native_runners(&self) -> &'static [&'static str] {
+        match (self.native_runner_os(), self.arch) {
+            (Some(RunnerOs::Linux), Arch::Aarch64) => &["ubuntu-24.04-arm", "ubuntu-22.04-arm"],
+            (Some(RunnerOs::Linux), _) => &["ubuntu-latest", "ubuntu-24.04", "ubuntu-22.04"],
+            (Some(RunnerOs::Windows), Arch::Aarch64) => &["windows-11-arm"],
+            (Some(RunnerOs::Windows), _) => &["windows-latest", "windows-2025", "windows-2022"],
+            (Some(RunnerOs::MacOs), Arch::Aarch64) => &["macos-latest", "macos-15", "macos-14"],
+            (Some(RunnerOs::MacOs), _) => &["macos-15-intel"],
+            (None, _) => &[],
+        }
+    }
+}
+
+impl Display for Target {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.arch)?;
+        if let Some(vendor) = self.vendor {
+            write!(f, "-{vendor}")?;
+        }
+        write!(f, "-{}", self.system)?;
+        if let Some(abi) = self.abi {
+            write!(f, "-{abi}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Target {
+    type Err = TargetError;
+
+    fn from_str(target: &str) -> std::result::Result<Self, Self::Err> {
+        let error = |reason: TargetErrorReason| TargetError { target: target.to_string(), reason };
+        let mut parts = target.split('-');
+
+        let arch = parts.next().unwrap_or_default();
+        let arch = Arch::parse(arch)
+            .ok_or_else(|| error(TargetErrorReason::UnknownArch(arch.to_string())))?;
+
+        let mut part = parts.next();
+        let vendor = part.and_then(Vendor::parse);
+        if vendor.is_some() {
+            part = parts.next();
+        }
+
+        let system = part.ok_or_else(|| error(TargetErrorReason::MissingSystem))?;
+        let system = System::parse(system)
+            .ok_or_else(|| error(TargetErrorReason::UnknownSystem(system.to_string())))?;
+
+        let abi = parts
+            .next()
+            .map(|abi| {
+                Abi::parse(abi).ok_or_else(|| error(TargetErrorReason::UnknownAbi(abi.to_string())))
+            })
+            .transpose()?;
+
+        if parts.next().is_some() {
+            return Err(error(TargetErrorReason::TooManyParts));
+        }
+
+        Ok(Self { arch, vendor, system, abi })
+    }
+}
+
+/// The error returned when a target triple can't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetError {
+    /// The target that failed to parse.
+    pub target: String,
+    pub reason: TargetErrorReason,
+}
+
+/// Why a target triple is invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TargetErrorReason {
+    UnknownArch(String),
+    UnknownSystem(String),
+    UnknownAbi(String),
+    MissingSystem,
+    TooManyParts,
+}
+
+impl Display for TargetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid target `{}`: ", self.target)?;
+        match &self.reason {
+            TargetErrorReason::UnknownArch(arch) => write!(f, "unknown architecture `{arch}`"),
+            TargetErrorReason::UnknownSystem(system) => {
+                write!(f, "unknown operating system `{system}`")
+            }
+            TargetErrorReason::UnknownAbi(abi) => write!(f, "unknown ABI `{abi}`"),
+            TargetErrorReason::MissingSystem => write!(f, "the operating system is missing"),
+            TargetErrorReason::TooManyParts => {
+                write!(f, "expected `arch[-vendor]-system[-abi]`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TargetError {}
+
 ///
 /// A Rust representation for the inputs of the setup-rust action.
 /// More information can be found [here](https://github.com/actions-rust-lang/setup-rust-toolchain/blob/main/action.yml).
@@ -147,8 +563,7 @@ pub struct Target {
 )]
 pub struct Toolchain {
     pub version: Vec<Version>,
-    #[setters(skip)]
-    pub target: Option<Target>,
+    pub targets: Vec<Target>,
     pub components: Vec<Component>,
     pub cache: Option<bool>,
     pub cache_directories: Vec<String>,
@@ -191,10 +606,102 @@ impl Toolchain {
         self
     }
 
-    pub fn target(mut self, arch: Arch, vendor: Vendor, system: System, abi: Option<Abi>) -> Self {
-        self.target = Some(Target { arch, vendor, system, abi });
+    pub fn add_target(mut self, target: Target) -> Self {
+        self.targets.push(target);
         self
     }
+
+    /// Creates a `Toolchain` pinned to the `rust-toolchain.toml`, or the
+    /// legacy `rust-toolchain`, file at the root of the git repository.
+    pub fn from_toolchain_file() -> Result<Self> {
+        Self::from_toolchain_file_in(root_dir()?)
+    }
+
+    /// Creates a `Toolchain` pinned to the toolchain file in `dir`. The
+    /// channel, components and targets set by the file replace the ones of
+    /// the toolchain, so the generated workflow can't drift from the pin.
+    pub fn from_toolchain_file_in<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        Self::default().pin_to_file_in(dir)
+    }
+
+    /// Replaces the version, components and targets with the ones pinned by
+    /// the toolchain file in `dir`. Those the file doesn't set are kept.
+    pub fn pin_to_file_in<P: AsRef<Path>>(mut self, dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        let path = ["rust-toolchain.toml", "rust-toolchain"]
+            .into_iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+            .ok_or_else(|| {
+                Error::manifest(
+                    dir.join("rust-toolchain.toml"),
+                    "no `rust-toolchain.toml` or `rust-toolchain` file found",
+                )
+            })?;
+        let content = std::fs::read_to_string(&path).map_err(|e| Error::io(e, &path))?;
+        let file = ToolchainFile::parse(&content).map_err(|e| Error::Manifest {
+            path: path.clone(),
+            reason: "not a valid toolchain file".to_string(),
+            source: Some(Box::new(e)),
+        })?;
+
+        if file.path.is_some() {
+            return Err(Error::manifest(
+                &path,
+                "custom toolchains set with `path` are not supported",
+            ));
+        }
+
+        if let Some(channel) = file.channel {
+            let Ok(version) = channel.parse();
+            self.version = vec![version];
+        }
+
+        if let Some(components) = file.components {
+            self.components = components
+                .iter()
+                .map(|component| component.parse())
+                .collect::<std::result::Result<_, _>>()
+                .map_err(|e: ComponentError| Error::manifest(&path, e))?;
+        }
+
+        if let Some(targets) = file.targets {
+            self.targets = targets
+                .iter()
+                .map(|target| target.parse())
+                .collect::<std::result::Result<_, _>>()
+                .map_err(|e: TargetError| Error::manifest(&path, e))?;
+        }
+
+        Ok(self)
+    }
+}
+
+/// The `[toolchain]` table of a toolchain file.
+#[derive(Deserialize, Default)]
+struct ToolchainFile {
+    channel: Option<String>,
+    path: Option<String>,
+    components: Option<Vec<String>>,
+    targets: Option<Vec<String>>,
+}
+
+impl ToolchainFile {
+    /// Parses a toolchain file. The legacy `rust-toolchain` file may only
+    /// contain the name of the channel.
+    fn parse(content: &str) -> std::result::Result<Self, toml::de::Error> {
+        #[derive(Deserialize)]
+        struct Root {
+            toolchain: ToolchainFile,
+        }
+
+        let channel = content.trim();
+        if !channel.is_empty() && !channel.contains(['\n', '=', '[']) {
+            return Ok(Self { channel: Some(channel.to_string()), ..Default::default() });
+        }
+
+        Ok(toml::from_str::<Root>(content)?.toolchain)
+    }
 }
 
 impl From<Toolchain> for Step<Use> {
@@ -208,13 +715,7 @@ impl From<Toolchain> for Step<Use> {
         let toolchain = value
             .version
             .iter()
-            .map(|t| match t {
-                Version::Stable => "stable".to_string(),
-                Version::Nightly => "nightly".to_string(),
-                Version::Custom((major, minor, patch)) => {
-                    format!("{major}.{minor}.{patch}")
-                }
-            })
+            .map(|t| t.to_string())
             .reduce(|acc, a| format!("{acc}, {a}"));
 
         let mut input = Input::default();
@@ -223,16 +724,15 @@ impl From<Toolchain> for Step<Use> {
             input = input.add("toolchain", toolchain);
         }
 
-        if let Some(target) = value.target {
-            let target = format!(
-                "{}-{}-{}{}",
-                target.arch,
-                target.vendor,
-                target.system,
-                target.abi.map(|v| v.to_string()).unwrap_or_default(),
-            );
+        if !value.targets.is_empty() {
+            let targets = value
+                .targets
+                .iter()
+                .map(|t| t.to_string())
+                .reduce(|acc, a| format!("{acc}, {a}"))
+                .unwrap_or_default();
 
-            input = input.add("target", target);
+            input = input.add("target", targets);
         }
 
         if !value.components.is_empty() {
@@ -292,3 +792,270 @@ impl From<Toolchain> for Step<Use> {
 }
 
 impl private::Sealed for Toolchain {}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::fixture::TempDir;
+
+    #[test]
+    fn test_target_round_trip() {
+        for target in [
+            "x86_64-unknown-linux-gnu",
+            "x86_64-unknown-linux-musl",
+            "x86_64-unknown-linux-gnux32",
+            "aarch64-unknown-linux-gnu",
+            "i686-unknown-linux-gnu",
+            "riscv64gc-unknown-linux-gnu",
+            "powerpc64le-unknown-linux-gnu",
+            "s390x-unknown-linux-gnu",
+            "loongarch64-unknown-linux-gnu",
+            "arm-unknown-linux-gnueabihf",
+            "armv7-unknown-linux-musleabihf",
+            "x86_64-apple-darwin",
+            "aarch64-apple-darwin",
+            "aarch64-apple-ios-sim",
+            "x86_64-pc-windows-msvc",
+            "aarch64-pc-windows-msvc",
+            "i686-pc-windows-gnu",
+            "x86_64-pc-windows-gnullvm",
+            "aarch64-linux-android",
+            "armv7-linux-androideabi",
+            "thumbv7neon-linux-androideabi",
+            "x86_64-unknown-freebsd",
+            "x86_64-unknown-illumos",
+            "sparcv9-sun-solaris",
+            "x86_64-fortanix-unknown-sgx",
+            "thumbv7em-none-eabihf",
+            "thumbv8m.main-none-eabi",
+            "riscv32imac-unknown-none-elf",
+            "x86_64-unknown-uefi",
+            "nvptx64-nvidia-cuda",
+            "wasm32-unknown-unknown",
+            "wasm32-unknown-emscripten",
+            "wasm32-wasip1",
+            "wasm32-wasip1-threads",
+            "wasm32-wasip2",
+            "armv7a-none-eabi",
+            "armv7r-none-eabi",
+            "armebv7r-none-eabi",
+            "riscv32imafc-unknown-none-elf",
+            "riscv64imac-unknown-none-elf",
+            "wasm32v1-none",
+            "aarch64-apple-ios-macabi",
+            "aarch64-unknown-none-softfloat",
+            "loongarch64-unknown-none-softfloat",
+        ] {
+            assert_eq!(target.parse::<Target>().unwrap().to_string(), target);
+        }
+    }
+
+    #[test]
+    fn test_target_fields() {
+        assert_eq!(
+            "x86_64-unknown-linux-gnu".parse::<Target>().unwrap(),
+            Target::X86_64_UNKNOWN_LINUX_GNU
+        );
+        assert_eq!(
+            "wasm32-wasip2".parse::<Target>().unwrap(),
+            Target::WASM32_WASIP2
+        );
+        assert_eq!(
+            "aarch64-linux-android".parse::<Target>().unwrap(),
+            Target {
+                arch: Arch::Aarch64,
+                vendor: None,
+                system: System::Linux,
+                abi: Some(Abi::Android),
+            }
+        );
+    }
+
+    #[test]
+    fn test_target_errors() {
+        let error = |target: &str| target.parse::<Target>().unwrap_err().to_string();
+
+        assert_eq!(
+            error("x86-unknown-linux-gnu"),
+            "invalid target `x86-unknown-linux-gnu`: unknown architecture `x86`"
+        );
+        assert_eq!(
+            error("x86_64-unknown-linux-gnux"),
+            "invalid target `x86_64-unknown-linux-gnux`: unknown ABI `gnux`"
+        );
+        assert_eq!(
+            error("x86_64-unknown"),
+            "invalid target `x86_64-unknown`: the operating system is missing"
+        );
+        assert_eq!(
+            error("x86_64-unknown-linux-gnu-extra"),
+            "invalid target `x86_64-unknown-linux-gnu-extra`: expected `arch[-vendor]-system[-abi]`"
+        );
+    }
+
+    #[test]
+    fn test_native_runners() {
+        assert_eq!(
+            Target::X86_64_UNKNOWN_LINUX_GNU.native_runner_os(),
+            Some(RunnerOs::Linux)
+        );
+        assert_eq!(
+            Target::AARCH64_APPLE_DARWIN.native_runners()[0],
+            "macos-latest"
+        );
+        assert_eq!(
+            Target::AARCH64_PC_WINDOWS_MSVC.native_runners(),
+            &["windows-11-arm"]
+        );
+        assert_eq!(
+            Target::X86_64_APPLE_DARWIN.native_runners(),
+            &["macos-15-intel"]
+        );
+        assert_eq!(Target::WASM32_WASIP1.native_runner_os(), None);
+        assert!(Target::WASM32_WASIP1.native_runners().is_empty());
+    }
+
+    #[test]
+    fn test_toolchain_target_input() {
+        let step: Step<Use> = Toolchain::default()
+            .add_stable()
+            .add_target(Target::X86_64_UNKNOWN_LINUX_GNU)
+            .add_target(Target::WASM32_WASIP2)
+            .into();
+
+        let expected = Step::new("Setup Rust Toolchain")
+            .uses("actions-rust-lang", "setup-rust-toolchain", "v1")
+            .with(
+                Input::default()
+                    .add("toolchain", "stable")
+                    .add("target", "x86_64-unknown-linux-gnu, wasm32-wasip2"),
+            );
+
+        assert_eq!(step, expected);
+    }
+
+    fn fixture(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name)
+    }
+
+    #[test]
+    fn test_from_toolchain_file() {
+        let toolchain = Toolchain::from_toolchain_file_in(fixture("toolchain")).unwrap();
+
+        assert_eq!(toolchain.version, vec![Version::new(1, 82, 0)]);
+        assert_eq!(
+            toolchain.components,
+            vec![Component::Clippy, Component::Rustfmt]
+        );
+        assert_eq!(
+            toolchain.targets,
+            vec![Target::X86_64_UNKNOWN_LINUX_MUSL, Target::WASM32_WASIP1]
+        );
+    }
+
+    #[test]
+    fn test_from_legacy_toolchain_file() {
+        let toolchain = Toolchain::default()
+            .add_stable()
+            .add_clippy()
+            .add_target(Target::WASM32_WASIP2)
+            .cache(true)
+            .pin_to_file_in(fixture("toolchain-legacy"))
+            .unwrap();
+
+        assert_eq!(
+            toolchain.version,
+            vec![Version::Channel("nightly-2024-05-01".to_string())]
+        );
+        assert_eq!(toolchain.components, vec![Component::Clippy]);
+        assert_eq!(toolchain.targets, vec![Target::WASM32_WASIP2]);
+        assert_eq!(toolchain.cache, Some(true));
+    }
+
+    #[test]
+    fn test_missing_toolchain_file() {
+        let result = Toolchain::from_toolchain_file_in(fixture("missing"));
+        assert!(matches!(result, Err(Error::Manifest { .. })));
+    }
+
+    #[test]
+    fn test_version_from_str() {
+        let version = |v: &str| v.parse::<Version>().unwrap();
+
+        assert_eq!(version("stable"), Version::Stable);
+        assert_eq!(version("beta"), Version::Beta);
+        assert_eq!(version("1.75.0"), Version::new(1, 75, 0));
+        assert_eq!(version("1.75"), Version::Channel("1.75".to_string()));
+    }
+
+    #[test]
+    fn test_msrv_from_package() {
+        let dir = TempDir::new(
+            "msrv-package",
+            &[(
+                "Cargo.toml",
+                "[package]\nname = \"app\"\nrust-version = \"1.75.0\"\n",
+            )],
+        );
+
+        assert_eq!(Version::msrv_in(&dir).unwrap(), Version::new(1, 75, 0));
+    }
+
+    #[test]
+    fn test_msrv_from_workspace() {
+        let dir = TempDir::new(
+            "msrv-workspace",
+            &[
+                (
+                    "Cargo.toml",
+                    "[workspace]\nmembers = [\"crates/*\"]\n\n[workspace.package]\nrust-version = \"1.80\"\n",
+                ),
+                (
+                    "crates/app/Cargo.toml",
+                    "[package]\nname = \"app\"\nrust-version.workspace = true\n",
+                ),
+            ],
+        );
+
+        assert_eq!(
+            Version::msrv_in(dir.join("crates/app")).unwrap(),
+            Version::Channel("1.80".to_string())
+        );
+        assert_eq!(
+            Version::msrv_in(&dir).unwrap(),
+            Version::Channel("1.80".to_string())
+        );
+    }
+
+    #[test]
+    fn test_msrv_missing() {
+        let dir = TempDir::new(
+            "msrv-missing",
+            &[("Cargo.toml", "[package]\nname = \"app\"\n")],
+        );
+
+        let error = Version::msrv_in(&dir).unwrap_err();
+        assert!(error
+            .to_string()
+            .ends_with("no `rust-version` is set for the package"));
+    }
+
+    #[test]
+    fn test_components() {
+        assert_eq!(Component::RustDoc.to_string(), "rust-docs");
+        for component in Component::ALL {
+            assert_eq!(
+                &component.to_string().parse::<Component>().unwrap(),
+                component
+            );
+        }
+        assert_eq!(
+            "rust-doc".parse::<Component>().unwrap_err().to_string(),
+            "unknown rustup component `rust-doc`"
+        );
+    }
+}
@@ -5,15 +5,20 @@ use derive_setters::Setters;
 use indexmap::IndexMap;
 use merge::Merge;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::concurrency::Concurrency;
 use crate::defaults::Defaults;
 // Import the moved types
 use crate::env::Env;
-use crate::error::Result;
+use crate::error::{unknown_keys, Diagnostic, Error, Result};
+use crate::format::{to_yaml, Format};
 use crate::generate::Generate;
 use crate::job::Job;
+use crate::lower::lower;
 use crate::permissions::Permissions;
+#[cfg(feature = "schema")]
+use crate::schema;
 use crate::Event;
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
@@ -88,6 +93,18 @@ pub struct Workflow {
     /// The maximum number of minutes a job can run before it is canceled.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timeout_minutes: Option<u32>,
+
+    /// Keys not modelled by this struct, preserved as-is.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
+
+    /// A comment written at the top of the workflow file.
+    #[serde(skip)]
+    pub comment: Option<String>,
+
+    /// Formatting hints used when the workflow is written as YAML.
+    #[serde(skip)]
+    pub format: Format,
 }
 
 /// Represents an action that can be triggered by an event in the workflow.
@@ -109,9 +126,12 @@ impl Workflow {
         Self { name: Some(name.to_string()), ..Default::default() }
     }
 
-    /// Converts the `Workflow` to a YAML string representation.
+    /// Converts the `Workflow` to a YAML string representation. The output
+    /// uses a fixed canonical style, see [`Format`] for the hints that can
+    /// adjust it. Keys GitHub doesn't support, such as [`crate::Artifacts`],
+    /// are lowered into equivalent steps first.
     pub fn to_string(&self) -> Result<String> {
-        Ok(serde_yml::to_string(self)?)
+        to_yaml(&lower(self)?)
     }
 
     /// Adds a job to the workflow with the specified ID and job configuration.
@@ -128,6 +148,69 @@ impl Workflow {
         Ok(serde_yml::from_str(yml)?)
     }
 
+    /// Parses a YAML string into a `Workflow`, failing with
+    /// [`Error::Validation`] if it contains keys that aren't modelled instead
+    /// of preserving them.
+    pub fn parse_strict(yml: &str) -> Result<Self> {
+        let workflow = Self::parse(yml)?;
+        let diagnostics = workflow.unknown_keys();
+        if diagnostics.is_empty() {
+            Ok(workflow)
+        } else {
+            Err(Error::Validation { workflow: workflow.name, diagnostics })
+        }
+    }
+
+    /// Validates the workflow against the GitHub workflow JSON schema,
+    /// failing with [`Error::Validation`] if GitHub would reject it.
+    /// Requires the `schema` feature.
+    #[cfg(feature = "schema")]
+    pub fn validate_schema(&self) -> Result<()> {
+        let diagnostics = schema::validate(&lower(self)?);
+        if diagnostics.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Validation { workflow: self.name.clone(), diagnostics })
+        }
+    }
+
+    /// Collects a diagnostic for every key that isn't modelled.
+    fn unknown_keys(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        unknown_keys("", &self.extra, &mut diagnostics);
+
+        if let Some(on) = &self.on {
+            on.unknown_keys(&mut diagnostics);
+        }
+
+        for (id, job) in self.jobs.iter().flat_map(|jobs| jobs.0.iter()) {
+            let path = format!("jobs.{id}");
+            unknown_keys(&path, &job.extra, &mut diagnostics);
+
+            if let Some(container) = &job.container {
+                unknown_keys(
+                    &format!("{path}.container"),
+                    &container.extra,
+                    &mut diagnostics,
+                );
+            }
+
+            for (name, service) in job.services.iter().flatten() {
+                unknown_keys(
+                    &format!("{path}.services.{name}"),
+                    &service.extra,
+                    &mut diagnostics,
+                );
+            }
+
+            for (i, step) in job.steps.iter().flatten().enumerate() {
+                unknown_keys(&format!("{path}.steps[{i}]"), &step.extra, &mut diagnostics);
+            }
+        }
+
+        diagnostics
+    }
+
     /// Generates the workflow using the `Generate` struct.
     pub fn generate(self) -> Result<()> {
         Generate::new(self).generate()
@@ -0,0 +1,662 @@
+//!
+//! Introspection of the local cargo workspace, read from the manifests alone
+//! so it works offline. It's used to generate per-crate jobs, path filters
+//! and publish steps that stay in sync with the workspace.
+
+use std::path::{Path, PathBuf};
+
+use indexmap::{IndexMap, IndexSet};
+
+use crate::error::{Error, Result};
+use crate::{Cargo, Expression, Job, Step};
+
+/// The id of the job added by [`Workspace::jobs_on_change`].
+pub const CHANGES_JOB: &str = "changes";
+
+/// The paths that affect every member of the workspace.
+const SHARED_PATHS: &[&str] = &[
+    "Cargo.toml",
+    "Cargo.lock",
+    "rust-toolchain*",
+    ".cargo/**",
+    ".github/workflows/**",
+];
+
+/// A package of the workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    /// The name of the package.
+    pub name: String,
+
+    /// The version of the package, if set.
+    pub version: Option<String>,
+
+    /// The directory of the package, relative to the workspace root.
+    pub path: PathBuf,
+
+    /// The `[features]` table of the package.
+    pub features: IndexMap<String, Vec<String>>,
+
+    /// Whether the package can be published, `false` with `publish = false`.
+    pub publish: bool,
+
+    /// The workspace packages this package depends on, including build and
+    /// target-specific dependencies.
+    pub dependencies: Vec<String>,
+
+    /// The workspace packages this package depends on for its tests.
+    pub dev_dependencies: Vec<String>,
+}
+
+impl Member {
+    /// The directory of the package with `/` separators, for eg:
+    /// `crates/gh-workflow`. Empty for a package at the workspace root.
+    pub fn dir(&self) -> String {
+        self.path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+/// A cargo workspace, or a single package without a workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    /// The directory of the root `Cargo.toml`.
+    pub root: PathBuf,
+
+    /// The packages of the workspace, in the order they are declared.
+    pub members: Vec<Member>,
+}
+
+impl Workspace {
+    /// Loads the workspace enclosing the package being built, or the
+    /// current directory when not run by cargo.
+    pub fn load() -> Result<Self> {
+        let dir = std::env::var_os("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .map_or_else(std::env::current_dir, Ok)?;
+
+        let mut package = None;
+        for dir in dir.ancestors() {
+            let path = dir.join("Cargo.toml");
+            if path.is_file() {
+                if read_manifest(&path)?.contains_key("workspace") {
+                    return Self::load_from(dir);
+                }
+                package.get_or_insert(dir);
+            }
+        }
+
+        let dir = package.ok_or_else(|| Error::manifest(dir.join("Cargo.toml"), "not found"))?;
+        Self::load_from(dir)
+    }
+
+    /// Loads the workspace whose root `Cargo.toml` is in `root`.
+    pub fn load_from<P: AsRef<Path>>(root: P) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        let manifest = read_manifest(&root.join("Cargo.toml"))?;
+        let workspace = manifest.get("workspace").and_then(toml::Value::as_table);
+
+        let mut dirs = IndexSet::new();
+        if manifest.contains_key("package") {
+            dirs.insert(PathBuf::new());
+        }
+
+        let patterns = |key: &str| {
+            workspace
+                .and_then(|workspace| workspace.get(key))
+                .and_then(toml::Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(toml::Value::as_str)
+                .collect::<Vec<_>>()
+        };
+        let excluded = patterns("exclude")
+            .into_iter()
+            .flat_map(|pattern| expand(&root, pattern))
+            .collect::<IndexSet<_>>();
+        for pattern in patterns("members") {
+            dirs.extend(
+                expand(&root, pattern)
+                    .into_iter()
+                    .filter(|dir| !excluded.contains(dir))
+                    .filter(|dir| root.join(dir).join("Cargo.toml").is_file()),
+            );
+        }
+
+        let inherited = workspace
+            .and_then(|workspace| workspace.get("package"))
+            .and_then(toml::Value::as_table);
+        let shared = workspace
+            .and_then(|workspace| workspace.get("dependencies"))
+            .and_then(toml::Value::as_table);
+
+        let manifests = dirs
+            .into_iter()
+            .map(|dir| {
+                let path = root.join(&dir).join("Cargo.toml");
+                let manifest = if dir.as_os_str().is_empty() {
+                    manifest.clone()
+                } else {
+                    read_manifest(&path)?
+                };
+                Ok((dir, path, manifest))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let names = manifests
+            .iter()
+            .map(|(_, path, manifest)| package_name(path, manifest))
+            .collect::<Result<IndexSet<_>>>()?;
+
+        let members = manifests
+            .into_iter()
+            .map(|(dir, path, manifest)| {
+                let package = manifest
+                    .get("package")
+                    .and_then(toml::Value::as_table)
+                    .ok_or_else(|| Error::manifest(&path, "missing `[package]`"))?;
+                let field = |key: &str| {
+                    let value = package.get(key)?;
+                    if is_inherited(value) {
+                        inherited?.get(key)
+                    } else {
+                        Some(value)
+                    }
+                };
+
+                let features = manifest
+                    .get("features")
+                    .and_then(toml::Value::as_table)
+                    .into_iter()
+                    .flatten()
+                    .map(|(feature, enables)| {
+                        let enables = enables
+                            .as_array()
+                            .into_iter()
+                            .flatten()
+                            .filter_map(toml::Value::as_str)
+                            .map(str::to_string)
+                            .collect();
+                        (feature.clone(), enables)
+                    })
+                    .collect();
+
+                // Dependencies are declared at the top level and in the
+                // `[target.'cfg(..)']` tables.
+                let targets = manifest
+                    .get("target")
+                    .and_then(toml::Value::as_table)
+                    .into_iter()
+                    .flat_map(|targets| targets.values())
+                    .filter_map(toml::Value::as_table);
+                let scopes = std::iter::once(&manifest)
+                    .chain(targets)
+                    .collect::<Vec<_>>();
+
+                let internal = |tables: &[&str]| {
+                    let mut dependencies = IndexSet::new();
+                    let deps = scopes.iter().flat_map(|scope| {
+                        tables
+                            .iter()
+                            .filter_map(|table| scope.get(*table).and_then(toml::Value::as_table))
+                    });
+                    for deps in deps {
+                        for (key, value) in deps {
+                            let name = dependency_name(key, value, shared);
+                            if names.contains(&name) {
+                                dependencies.insert(name);
+                            }
+                        }
+                    }
+                    dependencies.into_iter().collect()
+                };
+
+                Ok(Member {
+                    name: package_name(&path, &manifest)?,
+                    version: field("version")
+                        .and_then(toml::Value::as_str)
+                        .map(str::to_string),
+                    path: dir,
+                    features,
+                    publish: match field("publish") {
+                        Some(toml::Value::Boolean(publish)) => *publish,
+                        Some(toml::Value::Array(registries)) => !registries.is_empty(),
+                        _ => true,
+                    },
+                    dependencies: internal(&["dependencies", "build-dependencies"]),
+                    dev_dependencies: internal(&["dev-dependencies"]),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { root, members })
+    }
+
+    /// Finds a member by its package name.
+    pub fn member(&self, name: &str) -> Option<&Member> {
+        self.members.iter().find(|member| member.name == name)
+    }
+
+    /// The publishable members, ordered so that every package comes after
+    /// the workspace packages it depends on.
+    pub fn publish_order(&self) -> Result<Vec<&Member>> {
+        let mut ordered: IndexSet<&str> = IndexSet::new();
+        let mut pending = self
+            .members
+            .iter()
+            .filter(|member| member.publish)
+            .collect::<Vec<_>>();
+
+        while !pending.is_empty() {
+            let (ready, blocked): (Vec<_>, Vec<_>) = pending.into_iter().partition(|member| {
+                member.dependencies.iter().all(|dependency| {
+                    ordered.contains(dependency.as_str())
+                        || self.member(dependency).is_some_and(|dep| !dep.publish)
+                })
+            });
+
+            if ready.is_empty() {
+                let cycle = blocked
+                    .iter()
+                    .map(|member| member.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                return Err(Error::manifest(
+                    self.root.join("Cargo.toml"),
+                    format!("dependency cycle between {cycle}"),
+                ));
+            }
+
+            ordered.extend(ready.iter().map(|member| member.name.as_str()));
+            pending = blocked;
+        }
+
+        Ok(ordered
+            .into_iter()
+            .filter_map(|name| self.member(name))
+            .collect())
+    }
+
+    /// One `cargo publish` command per publishable member, in publish
+    /// order.
+    pub fn publish_commands(&self) -> Result<Vec<Cargo>> {
+        Ok(self
+            .publish_order()?
+            .into_iter()
+            .map(|member| {
+                Cargo::new("publish")
+                    .name(format!("Publish {}", member.name))
+                    .add_package(&member.name)
+            })
+            .collect())
+    }
+
+    /// The paths whose changes affect `member`: its own directory, the
+    /// directories of the workspace packages it depends on and the files
+    /// shared by every package, like the root manifest, the lockfile, the
+    /// toolchain file, the cargo config and the workflows themselves.
+    pub fn paths(&self, member: &Member) -> Vec<String> {
+        let mut members = IndexSet::new();
+        let mut stack = vec![member];
+        while let Some(member) = stack.pop() {
+            if members.insert(member.name.as_str()) {
+                stack.extend(
+                    member
+                        .dependencies
+                        .iter()
+                        .chain(&member.dev_dependencies)
+                        .filter_map(|name| self.member(name)),
+                );
+            }
+        }
+
+        let mut paths = members
+            .into_iter()
+            .filter_map(|name| self.member(name))
+            .map(|member| match member.dir() {
+                dir if dir.is_empty() => "**".to_string(),
+                dir => format!("{dir}/**"),
+            })
+            .collect::<IndexSet<_>>();
+        paths.extend(SHARED_PATHS.iter().map(|path| path.to_string()));
+        paths.into_iter().collect()
+    }
+
+    /// One job per member, keyed by the package name.
+    pub fn jobs<F: FnMut(&Member) -> Job>(&self, mut f: F) -> IndexMap<String, Job> {
+        self.members
+            .iter()
+            .map(|member| (member.name.clone(), f(member)))
+            .collect()
+    }
+
+    /// A job that detects which members are affected by the changes of the
+    /// event, with one `true`/`false` output per member.
+    pub fn changes_job(&self) -> Job {
+        let filters = self
+            .members
+            .iter()
+            .map(|member| {
+                let paths = self
+                    .paths(member)
+                    .into_iter()
+                    .map(|path| format!("  - '{path}'\n"))
+                    .collect::<String>();
+                format!("{}:\n{paths}", member.name)
+            })
+            .collect::<String>();
+
+        // The checkout is only needed on push events, pull requests are
+        // compared through the API.
+        let mut job = Job::new("Detect Changes")
+            .add_step(Step::checkout())
+            .add_step(
+                Step::new("Detect Changes")
+                    .uses("dorny", "paths-filter", "v3")
+                    .id(CHANGES_JOB)
+                    .add_with(("filters", filters)),
+            );
+        for member in &self.members {
+            job = job.add_output(
+                &member.name,
+                format!("${{{{ steps.{CHANGES_JOB}.outputs['{}'] }}}}", member.name),
+            );
+        }
+        job
+    }
+
+    /// Like [`Workspace::jobs`], along with the [`Workspace::changes_job`]
+    /// keyed by [`CHANGES_JOB`]. Each member job only runs when the changes
+    /// affect that member.
+    pub fn jobs_on_change<F: FnMut(&Member) -> Job>(&self, f: F) -> IndexMap<String, Job> {
+        let mut jobs = IndexMap::new();
+        jobs.insert(CHANGES_JOB.to_string(), self.changes_job());
+        jobs.extend(self.jobs(f).into_iter().map(|(name, job)| {
+            let changed = format!("needs.{CHANGES_JOB}.outputs['{name}'] == 'true'");
+            let cond = match &job.cond {
+                Some(cond) => format!("({changed}) && ({})", cond.0),
+                None => changed,
+            };
+            let job = job.add_needs(CHANGES_JOB).cond(Expression::new(cond));
+            (name, job)
+        }));
+        jobs
+    }
+}
+
+/// Reads and parses the `Cargo.toml` at `path`.
+pub(crate) fn read_manifest(path: &Path) -> Result<toml::Table> {
+    let content = std::fs::read_to_string(path).map_err(|e| Error::io(e, path))?;
+    toml::from_str(&content).map_err(|e| Error::Manifest {
+        path: path.to_path_buf(),
+        reason: "not a valid `Cargo.toml`".to_string(),
+        source: Some(Box::new(e)),
+    })
+}
+
+fn package_name(path: &Path, manifest: &toml::Table) -> Result<String> {
+    manifest
+        .get("package")
+        .and_then(|package| package.get("name"))
+        .and_then(toml::Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| Error::manifest(path, "missing `package.name`"))
+}
+
+/// Whether `value` is `{ workspace = true }`.
+fn is_inherited(value: &toml::Value) -> bool {
+    value
+        .get("workspace")
+        .and_then(toml::Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// The package name of a dependency, which differs from its key when it's
+/// renamed with `package = "..."`.
+fn dependency_name(key: &str, value: &toml::Value, shared: Option<&toml::Table>) -> String {
+    let value = if is_inherited(value) {
+        shared.and_then(|shared| shared.get(key)).unwrap_or(value)
+    } else {
+        value
+    };
+    value
+        .get("package")
+        .and_then(toml::Value::as_str)
+        .unwrap_or(key)
+        .to_string()
+}
+
+/// Expands a `members` pattern, which may use `*` and `?` in any of its
+/// segments, into directories relative to `root`.
+fn expand(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    let mut dirs = vec![PathBuf::new()];
+    for segment in pattern.split('/').filter(|segment| !segment.is_empty()) {
+        if !segment.contains(['*', '?']) {
+            dirs.iter_mut().for_each(|dir| dir.push(segment));
+            continue;
+        }
+
+        dirs = dirs
+            .into_iter()
+            .flat_map(|dir| {
+                let mut entries = std::fs::read_dir(root.join(&dir))
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .filter(|entry| entry.path().is_dir())
+                    .filter_map(|entry| entry.file_name().into_string().ok())
+                    .filter(|name| matches(segment, name))
+                    .collect::<Vec<_>>();
+                entries.sort();
+                entries.into_iter().map(move |name| dir.join(name))
+            })
+            .collect();
+    }
+    dirs
+}
+
+/// Matches `name` against a pattern where `*` matches any run of characters
+/// and `?` a single one.
+pub(crate) fn matches(pattern: &str, name: &str) -> bool {
+    match pattern.chars().next() {
+        None => name.is_empty(),
+        Some('*') => {
+            let rest = &pattern[1..];
+            name.char_indices()
+                .map(|(index, _)| index)
+                .chain([name.len()])
+                .any(|index| matches(rest, &name[index..]))
+        }
+        Some(c) => {
+            let mut chars = name.chars();
+            if chars.next().is_some_and(|n| c == '?' || c == n) {
+                matches(&pattern[c.len_utf8()..], chars.as_str())
+            } else {
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_snapshot;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::fixture::TempDir;
+    use crate::Workflow;
+
+    fn fixture() -> Workspace {
+        let root = TempDir::new(
+            "workspace",
+            &[
+                (
+                    "Cargo.toml",
+                    r#"
+[workspace]
+members = ["crates/*", "tools/xtask"]
+exclude = ["crates/scratch"]
+
+[workspace.package]
+version = "1.2.0"
+
+[workspace.dependencies]
+core-lib = { path = "crates/core", version = "1.2.0", package = "app-core" }
+"#,
+                ),
+                (
+                    "crates/core/Cargo.toml",
+                    r#"
+[package]
+name = "app-core"
+version.workspace = true
+
+[features]
+default = ["std"]
+std = []
+serde = []
+"#,
+                ),
+                (
+                    "crates/cli/Cargo.toml",
+                    r#"
+[package]
+name = "app-cli"
+version.workspace = true
+
+[dependencies]
+core-lib = { workspace = true }
+
+[target.'cfg(unix)'.dependencies]
+app-macros = { path = "../macros" }
+"#,
+                ),
+                (
+                    "crates/macros/Cargo.toml",
+                    r#"
+[package]
+name = "app-macros"
+version = "0.1.0"
+
+[dev-dependencies]
+core-lib = { workspace = true }
+"#,
+                ),
+                (
+                    "tools/xtask/Cargo.toml",
+                    r#"
+[package]
+name = "xtask"
+publish = false
+
+[dependencies]
+app-cli = { path = "../../crates/cli" }
+"#,
+                ),
+                (
+                    "crates/scratch/Cargo.toml",
+                    "[package]\nname = \"scratch\"\n",
+                ),
+            ],
+        );
+        Workspace::load_from(&root).unwrap()
+    }
+
+    #[test]
+    fn test_members() {
+        let workspace = fixture();
+        let names = workspace
+            .members
+            .iter()
+            .map(|member| member.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["app-cli", "app-core", "app-macros", "xtask"]);
+
+        let cli = workspace.member("app-cli").unwrap();
+        assert_eq!(cli.version.as_deref(), Some("1.2.0"));
+        assert_eq!(cli.dir(), "crates/cli");
+        assert_eq!(cli.dependencies, ["app-core", "app-macros"]);
+
+        let core = workspace.member("app-core").unwrap();
+        assert_eq!(core.features["default"], ["std"]);
+        assert_eq!(
+            workspace.member("app-macros").unwrap().dev_dependencies,
+            ["app-core"]
+        );
+        assert!(!workspace.member("xtask").unwrap().publish);
+    }
+
+    #[test]
+    fn test_publish_order() {
+        let workspace = fixture();
+        let order = workspace
+            .publish_order()
+            .unwrap()
+            .into_iter()
+            .map(|member| member.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(order, ["app-core", "app-macros", "app-cli"]);
+    }
+
+    #[test]
+    fn test_paths() {
+        let workspace = fixture();
+        let macros = workspace.member("app-macros").unwrap();
+        assert_eq!(
+            workspace.paths(macros),
+            [
+                "crates/macros/**",
+                "crates/core/**",
+                "Cargo.toml",
+                "Cargo.lock",
+                "rust-toolchain*",
+                ".cargo/**",
+                ".github/workflows/**"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_jobs_on_change() {
+        let workspace = fixture();
+        let jobs = workspace.jobs_on_change(|member| {
+            Job::new(format!("Test {}", member.name))
+                .add_step(Step::checkout())
+                .add_step(Cargo::new("test").add_package(&member.name))
+        });
+
+        let workflow = jobs
+            .into_iter()
+            .fold(Workflow::new("CI"), |workflow, (id, job)| {
+                workflow.add_job(id, job)
+            });
+        assert_snapshot!(workflow.to_string().unwrap());
+    }
+
+    #[test]
+    fn test_load_this_workspace() {
+        let workspace = Workspace::load().unwrap();
+        let order = workspace
+            .publish_order()
+            .unwrap()
+            .into_iter()
+            .map(|member| member.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            order,
+            ["gh-workflow-macros", "gh-workflow", "gh-workflow-tailcall"]
+        );
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("*", "core"));
+        assert!(matches("app-*", "app-core"));
+        assert!(matches("a?p", "app"));
+        assert!(!matches("app-*", "core"));
+    }
+}
@@ -0,0 +1,62 @@
+use gh_workflow::{action, Input, Step, Use};
+use pretty_assertions::assert_eq;
+
+/// The `actions/cache` action, generated from its vendored metadata.
+#[action(path = "tests/fixtures/action-cache.yml", uses = "actions/cache@v4")]
+pub struct CacheAction;
+
+#[test]
+fn test_action_required_inputs() {
+    let step: Step<Use> = CacheAction::new("target", "cargo-${{ runner.os }}").into();
+
+    let expected = Step::new("Cache").uses("actions", "cache", "v4").with(
+        Input::default()
+            .add("path", "target")
+            .add("key", "cargo-${{ runner.os }}"),
+    );
+
+    assert_eq!(step, expected);
+}
+
+#[test]
+fn test_action_optional_inputs_and_outputs() {
+    let cache = CacheAction::new("target", "cargo");
+    assert!(cache.outputs().is_none());
+
+    let cache = cache
+        .id("cache")
+        .lookup_only(true)
+        .enable_cross_os_archive(true);
+
+    assert_eq!(
+        cache.outputs().unwrap().cache_hit().to_string(),
+        "${{ steps.cache.outputs.cache-hit }}"
+    );
+
+    let step: Step<Use> = cache.into();
+    let with = step.value.with.unwrap();
+
+    assert_eq!(with.0.get("lookup-only"), Some(&true.into()));
+    assert_eq!(with.0.get("enableCrossOsArchive"), Some(&true.into()));
+}
+
+#[test]
+fn test_action_defaults() {
+    assert_eq!(
+        CacheAction::DEFAULTS,
+        &[
+            ("enableCrossOsArchive", "false"),
+            ("fail-on-cache-miss", "false"),
+            ("lookup-only", "false"),
+            ("save-always", "false"),
+        ]
+    );
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_action_deprecated_input() {
+    let step: Step<Use> = CacheAction::new("target", "cargo").save_always(true).into();
+
+    assert!(step.value.with.unwrap().0.contains_key("save-always"));
+}
@@ -0,0 +1,66 @@
+use gh_workflow::ctx::Context;
+use gh_workflow::{Event, InputChoice, Job, Workflow, WorkflowInputs};
+use insta::assert_snapshot;
+use pretty_assertions::assert_eq;
+
+#[allow(unused)]
+#[derive(InputChoice)]
+enum DeployEnv {
+    Staging,
+    Production,
+}
+
+#[derive(WorkflowInputs)]
+struct DeployInputs {
+    /// The environment to deploy to.
+    #[input(default = "staging")]
+    environment: DeployEnv,
+    /// Only print what would be deployed.
+    #[input(default = false, rename = "dry-run")]
+    dry_run: bool,
+    /// The number of replicas.
+    replicas: Option<u32>,
+}
+
+#[test]
+fn test_workflow_dispatch_inputs() {
+    let workflow = Workflow::new("Deploy")
+        .on(Event::default()
+            .workflow_dispatch(DeployInputs::workflow_dispatch())
+            .workflow_call(DeployInputs::workflow_call()))
+        .add_job("deploy", Job::new("Deploy"));
+
+    #[cfg(feature = "schema")]
+    workflow.validate_schema().unwrap();
+    assert_snapshot!(workflow.to_string().unwrap());
+}
+
+#[test]
+fn test_inputs_context() {
+    let inputs = DeployInputs::context();
+
+    assert_eq!(inputs.to_string(), "${{ inputs }}");
+    assert_eq!(inputs.dry_run().to_string(), "${{ inputs.dry-run }}");
+    assert_eq!(
+        Context::<DeployInputs>::inputs().replicas().to_string(),
+        "${{ inputs.replicas }}"
+    );
+}
+
+#[test]
+fn test_inputs_with() {
+    let inputs = DeployInputs {
+        environment: DeployEnv::Production,
+        dry_run: true,
+        replicas: None,
+    };
+
+    let job = Job::default()
+        .uses("./.github/workflows/deploy.yml")
+        .with(inputs.to_input());
+    let with = job.with.unwrap();
+
+    assert_eq!(with.0.get("environment"), Some(&"production".into()));
+    assert_eq!(with.0.get("dry-run"), Some(&true.into()));
+    assert_eq!(with.0.get("replicas"), None);
+}
@@ -1,3 +1,4 @@
+use gh_workflow::error::Error;
 use gh_workflow::Workflow;
 use pretty_assertions::assert_eq;
 use serde_json::Value;
@@ -39,3 +40,58 @@ fn test_workflow_secrets() {
     let (actual, expected) = split(include_str!("./fixtures/workflow-secrets.yml"));
     assert_eq!(actual, expected);
 }
+
+#[test]
+fn test_workflow_unknown_keys() {
+    let (actual, expected) = split(include_str!("./fixtures/workflow-unknown-keys.yml"));
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_workflow_unknown_keys_strict() {
+    let error =
+        Workflow::parse_strict(include_str!("./fixtures/workflow-unknown-keys.yml")).unwrap_err();
+
+    let Error::Validation { diagnostics, .. } = error else {
+        panic!("expected a validation error, got: {error}");
+    };
+    let paths = diagnostics
+        .iter()
+        .map(|d| d.path.as_str())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        paths,
+        vec![
+            "on.future_event",
+            "on.push.tags-ignore",
+            "jobs.build.snapshot",
+            "jobs.build.container.future-option",
+            "jobs.build.steps[0].future-step-key",
+        ]
+    );
+}
+
+#[test]
+fn test_workflow_strict_accepts_known_keys() {
+    assert!(Workflow::parse_strict(include_str!("./fixtures/workflow-ci.yml")).is_ok());
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn test_workflow_fixtures_match_schema() {
+    let fixtures = [
+        include_str!("./fixtures/workflow-bench.yml"),
+        include_str!("./fixtures/workflow-ci.yml"),
+        include_str!("./fixtures/workflow-demo.yml"),
+        include_str!("./fixtures/workflow-rust.yml"),
+        include_str!("./fixtures/workflow-secrets.yml"),
+    ];
+
+    for fixture in fixtures {
+        let workflow = Workflow::parse(fixture).unwrap();
+        if let Err(error) = workflow.validate_schema() {
+            panic!("{error}");
+        }
+    }
+}
@@ -0,0 +1,9 @@
+use gh_workflow_macros::Context;
+
+#[derive(Context)]
+#[context(rename = "deploy")]
+struct Deploy {
+    environment: String,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use gh_workflow_macros::Context;
+
+#[derive(Context)]
+struct Deploy {
+    #[context(root)]
+    environment: String,
+}
+
+fn main() {}
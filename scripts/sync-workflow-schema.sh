@@ -0,0 +1,35 @@
+#!/usr/bin/env bash
+# Vendors the GitHub workflow JSON schema from SchemaStore, unchanged.
+#
+# Usage: scripts/sync-workflow-schema.sh [<commit>]
+#
+# Without a commit, the latest commit of the default branch is used. The
+# schema is written to crates/gh-workflow/schema/github-workflow.json and the
+# commit it was taken from to crates/gh-workflow/schema/SOURCE. Run
+# `cargo test --workspace --all-features` afterwards, the generated workflows
+# must still validate.
+
+set -euo pipefail
+
+REPO="SchemaStore/schemastore"
+FILE="src/schemas/json/github-workflow.json"
+DIR="$(git rev-parse --show-toplevel)/crates/gh-workflow/schema"
+
+COMMIT="${1:-$(curl -fsSL "https://api.github.com/repos/$REPO/commits/HEAD" |
+  sed -n 's/^  "sha": "\([0-9a-f]\{40\}\)",$/\1/p' | head -n 1)}"
+
+if [ -z "$COMMIT" ]; then
+  echo "error: could not resolve the latest commit of $REPO" >&2
+  exit 1
+fi
+
+curl -fsSL "https://raw.githubusercontent.com/$REPO/$COMMIT/$FILE" -o "$DIR/github-workflow.json"
+
+cat > "$DIR/SOURCE" <<EOF
+repository: https://github.com/$REPO
+path: $FILE
+commit: $COMMIT
+license: Apache-2.0
+EOF
+
+echo "Vendored $FILE at $REPO@$COMMIT"